@@ -0,0 +1,370 @@
+//! Points, directions and grids on the integer plane.
+//!
+//! `y` grows downwards like rows on a screen, so `Up` is `y - 1`. Puzzles
+//! that have `y` pointing up flip it when they print coordinates.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// The point `n` steps away in direction `dir`.
+    pub fn step(self, dir: Direction, n: i64) -> Point {
+        let d = dir.delta();
+        Point::new(self.x + d.x * n, self.y + d.y * n)
+    }
+
+    /// The four points next to this one, clockwise from above.
+    pub fn neighbors(self) -> impl Iterator<Item = Point> {
+        Direction::ALL.iter().map(move |&dir| self + dir.delta())
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise from `Up`.
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn delta(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn turn_right(self) -> Direction {
+        Direction::ALL[(self.index() + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Direction {
+        Direction::ALL[(self.index() + 3) % 4]
+    }
+
+    pub fn reverse(self) -> Direction {
+        Direction::ALL[(self.index() + 2) % 4]
+    }
+
+    /// Reads `U`, `R`, `D` and `L`, compass points `N`, `E`, `S` and `W`, or
+    /// one of the arrows `^`, `>`, `v` and `<`.
+    pub fn parse(c: char) -> Option<Direction> {
+        match c {
+            'U' | 'N' | '^' => Some(Direction::Up),
+            'R' | 'E' | '>' => Some(Direction::Right),
+            'D' | 'S' | 'v' => Some(Direction::Down),
+            'L' | 'W' | '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    /// The arrow pointing this way.
+    pub fn arrow(self) -> char {
+        ['^', '>', 'v', '<'][self.index()]
+    }
+}
+
+/// The smallest rectangle holding some points, edges included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(p: Point) -> Bounds {
+        Bounds { min: p, max: p }
+    }
+
+    /// The bounds of `points`, or `None` if there are none.
+    pub fn of<I: IntoIterator<Item = Point>>(points: I) -> Option<Bounds> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Bounds::new(first), Bounds::include))
+    }
+
+    /// These bounds grown to hold `p` as well.
+    pub fn include(self, p: Point) -> Bounds {
+        Bounds {
+            min: Point::new(self.min.x.min(p.x), self.min.y.min(p.y)),
+            max: Point::new(self.max.x.max(p.x), self.max.y.max(p.y)),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    /// Every point inside, row by row.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        (self.min.y..=self.max.y).flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Point::new(x, y)))
+    }
+}
+
+/// Lines of `width` characters, each ending in a newline.
+fn render_rows<I: Iterator<Item = char>>(width: usize, chars: I) -> String {
+    let mut out = String::new();
+    for (i, c) in chars.enumerate() {
+        out.push(c);
+        if (i + 1) % width == 0 {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// A rectangle of cells with its top left corner at the origin, stored row
+/// by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid of `cells` given row by row, which must hold exactly
+    /// `width * height` of them.
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), width * height, "cells do not fill a {}x{} grid", width, height);
+        Grid { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds { min: Point::ORIGIN, max: Point::new(self.width as i64 - 1, self.height as i64 - 1) }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        if self.contains(p) { Some(p.y as usize * self.width + p.x as usize) } else { None }
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(move |i| &mut self.cells[i])
+    }
+
+    /// The cells row by row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width.max(1);
+        self.cells.iter().enumerate().map(move |(i, cell)| (Point::new((i % width) as i64, (i / width) as i64), cell))
+    }
+
+    /// The neighbors of `p` that are inside the grid.
+    pub fn neighbors(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbors().filter(move |&n| self.contains(n))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// One line of text per row, with a character for every cell.
+    pub fn render<F: FnMut(&T) -> char>(&self, f: F) -> String {
+        render_rows(self.width, self.cells.iter().map(f))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, width, height))
+    }
+}
+
+/// Cells anywhere on the plane, only storing the ones that were set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> SparseGrid<T> {
+        SparseGrid { cells: HashMap::new() }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn insert(&mut self, p: Point, value: T) -> Option<T> {
+        self.cells.insert(p, value)
+    }
+
+    /// The cells that were set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&p, cell)| (p, cell))
+    }
+
+    /// The bounds of the cells that were set.
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::of(self.cells.keys().copied())
+    }
+
+    /// The cells inside `bounds` as a dense grid, with `f` giving the value
+    /// of each from what was set there.
+    pub fn crop<U, F: FnMut(Option<&T>) -> U>(&self, bounds: Bounds, mut f: F) -> Grid<U> {
+        let cells = bounds.points().map(|p| f(self.cells.get(&p))).collect();
+        Grid::from_cells(bounds.width(), bounds.height(), cells)
+    }
+
+    /// One line of text per row of `bounds`, with a character for every
+    /// point.
+    pub fn render<F: FnMut(Point, Option<&T>) -> char>(&self, bounds: Bounds, mut f: F) -> String {
+        render_rows(bounds.width(), bounds.points().map(|p| f(p, self.cells.get(&p))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points() {
+        let p = Point::new(3, -4);
+        assert_eq!(p.manhattan(Point::ORIGIN), 7);
+        assert_eq!(p.step(Direction::Up, 2), Point::new(3, -6));
+        assert_eq!(p - Point::new(1, 1) + Point::new(0, 2), Point::new(2, -3));
+        assert_eq!(p.neighbors().collect::<Vec<_>>(),
+                   vec![Point::new(3, -5), Point::new(4, -4), Point::new(3, -3), Point::new(2, -4)]);
+        let dir = Direction::parse('L').unwrap();
+        assert_eq!((dir.turn_left(), dir.turn_right(), dir.reverse()), (Direction::Down, Direction::Up, Direction::Right));
+        assert_eq!(Direction::ALL.iter().map(|d| Direction::parse(d.arrow())).collect::<Vec<_>>(),
+                   Direction::ALL.iter().copied().map(Some).collect::<Vec<_>>());
+        assert_eq!(Direction::parse('x'), None);
+        let bounds = Bounds::of(vec![Point::new(2, 1), Point::new(-1, 3)]).unwrap();
+        assert_eq!((bounds.min, bounds.max, bounds.width(), bounds.height()), (Point::new(-1, 1), Point::new(2, 3), 4, 3));
+        assert!(bounds.contains(Point::new(0, 2)) && !bounds.contains(Point::new(0, 0)));
+        assert_eq!(Bounds::of(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_grids() {
+        let mut grid = Grid::new(3, 2, '.');
+        grid[Point::new(2, 1)] = '#';
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.render(|&c| c), "...\n..#\n");
+        assert_eq!(grid.neighbors(Point::new(2, 1)).collect::<Vec<_>>(), vec![Point::new(2, 0), Point::new(1, 1)]);
+        assert_eq!(grid.iter().filter(|&(_, &c)| c == '#').map(|(p, _)| p).collect::<Vec<_>>(), vec![Point::new(2, 1)]);
+        assert_eq!(grid.rows().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>(), vec!["...", "..#"]);
+
+        let mut sparse = SparseGrid::new();
+        sparse.insert(Point::new(-1, -1), 1);
+        sparse.insert(Point::new(1, 0), 0);
+        let bounds = sparse.bounds().unwrap();
+        assert_eq!(sparse.render(bounds, |_, c| if c == Some(&1) { '#' } else { '.' }), "#..\n...\n");
+        assert_eq!(sparse.crop(bounds, |c| c.copied()), Grid::from_cells(3, 2, vec![Some(1), None, None, None, None, Some(0)]));
+    }
+}
@@ -0,0 +1,295 @@
+//! Reading and writing black and white pictures as image files.
+//!
+//! Pixels use the Space Image Format digits: 0 is black, 1 is white and 2 is
+//! transparent. PNG and GIF are written with the `png` and `gif` crates.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::grid::Grid;
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+pub const TRANSPARENT: u8 = 2;
+
+const RGBA: [[u8; 4]; 3] = [[0, 0, 0, 255], [255, 255, 255, 255], [128, 128, 128, 0]];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Picture {
+    pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Picture {
+        assert_eq!(pixels.len(), width * height, "picture size does not match its pixels");
+        Picture { width, height, pixels }
+    }
+
+    /// White where `rows` is lit, black elsewhere.
+    pub fn from_rows(rows: &[Vec<bool>]) -> Picture {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let pixels = rows.iter()
+            .flat_map(|row| (0..width).map(move |x| if row.get(x) == Some(&true) { WHITE } else { BLACK }))
+            .collect();
+        Picture::new(width, rows.len(), pixels)
+    }
+
+    pub fn from_grid(grid: &Grid<u8>) -> Picture {
+        Picture::new(grid.width(), grid.height(), grid.cells().to_vec())
+    }
+
+    /// Plain (P1) or raw (P4) PBM, where a set bit is black.
+    pub fn from_pbm(data: &[u8]) -> io::Result<Picture> {
+        let mut pos = 0;
+        let mut token = || -> io::Result<String> {
+            loop {
+                match data.get(pos) {
+                    Some(b'#') => while pos < data.len() && data[pos] != b'\n' { pos += 1 },
+                    Some(c) if c.is_ascii_whitespace() => pos += 1,
+                    _ => break,
+                }
+            }
+            let start = pos;
+            while pos < data.len() && !data[pos].is_ascii_whitespace() && data[pos] != b'#' {
+                pos += 1;
+            }
+            match &data[start..pos] {
+                [] => Err(invalid("PBM ends too early")),
+                t => Ok(String::from_utf8_lossy(t).into_owned()),
+            }
+        };
+        let magic = token()?;
+        let mut number = || token()?.parse::<usize>().map_err(|_| invalid("bad PBM size"));
+        let (width, height) = (number()?, number()?);
+        let size = width.checked_mul(height).ok_or_else(|| invalid("PBM too large"))?;
+        let pixels = match magic.as_str() {
+            "P1" => {
+                // every pixel takes a byte, so no more than that can come
+                let mut pixels = Vec::with_capacity(size.min(data.len()));
+                while pixels.len() < size {
+                    // plain PBM digits need not be separated
+                    for c in token()?.bytes() {
+                        pixels.push(match c {
+                            b'0' => WHITE,
+                            b'1' => BLACK,
+                            _ => return Err(invalid("bad PBM pixel")),
+                        });
+                    }
+                }
+                pixels.truncate(size);
+                pixels
+            }
+            "P4" => {
+                let start = pos + 1;
+                let row_bytes = width.div_ceil(8);
+                let end = row_bytes.checked_mul(height).and_then(|n| n.checked_add(start)).ok_or_else(|| invalid("PBM too large"))?;
+                let raster = data.get(start..end).ok_or_else(|| invalid("PBM ends too early"))?;
+                raster.chunks(row_bytes)
+                    .flat_map(|row| (0..width).map(move |x| if row[x / 8] & (0x80 >> (x % 8)) != 0 { BLACK } else { WHITE }))
+                    .collect()
+            }
+            _ => return Err(invalid("not a PBM file")),
+        };
+        Ok(Picture::new(width, height, pixels))
+    }
+
+    /// Pixels that are more than half transparent become transparent, the
+    /// rest black or white by their brightness.
+    pub fn from_png(data: &[u8]) -> io::Result<Picture> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(|e| invalid(&e.to_string()))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(|e| invalid(&e.to_string()))?;
+        let channels = info.color_type.samples();
+        let pixels = buf[..info.buffer_size()].chunks(info.line_size).flat_map(|line| {
+            line.chunks(channels).take(info.width as usize).map(|px| {
+                let (gray, alpha) = match px {
+                    [g] => (*g as u32, 255),
+                    [g, a] => (*g as u32, *a),
+                    [r, g, b] => ((*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000, 255),
+                    [r, g, b, a] => ((*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000, *a),
+                    _ => unreachable!(),
+                };
+                if alpha < 128 { TRANSPARENT } else if gray < 128 { BLACK } else { WHITE }
+            }).collect::<Vec<_>>()
+        }).collect();
+        Ok(Picture::new(info.width as usize, info.height as usize, pixels))
+    }
+
+    /// Reads a `pbm` or `png` file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Picture> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("pbm") => Picture::from_pbm(&data),
+            Some("png") => Picture::from_png(&data),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                    format!("{}: unknown image format, use pbm or png", path.display()))),
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x].min(TRANSPARENT)
+    }
+
+    /// Scaled RGBA rows.
+    fn rgba_rows(&self, scale: usize) -> Vec<Vec<u8>> {
+        (0..self.height * scale).map(|y| {
+            (0..self.width * scale).flat_map(|x| RGBA[self.get(x / scale, y / scale) as usize].to_vec()).collect()
+        }).collect()
+    }
+
+    /// Binary PPM (P6). PPM has no transparency, so transparent pixels are gray.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width * scale, self.height * scale).into_bytes();
+        for row in self.rgba_rows(scale) {
+            for px in row.chunks(4) {
+                out.extend_from_slice(&px[..3]);
+            }
+        }
+        out
+    }
+
+    /// 8 bit RGBA. Fails for an empty picture, which PNG can not hold.
+    pub fn to_png(&self, scale: usize) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, (self.width * scale) as u32, (self.height * scale) as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| invalid(&e.to_string()))?;
+        writer.write_image_data(&self.rgba_rows(scale).concat()).map_err(|e| invalid(&e.to_string()))?;
+        writer.finish().map_err(|e| invalid(&e.to_string()))?;
+        Ok(out)
+    }
+
+    /// One white square per lit pixel on a black background.
+    pub fn to_svg(&self, scale: usize) -> String {
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+             <rect width=\"{w}\" height=\"{h}\" fill=\"black\"/>\n",
+            w = self.width * scale, h = self.height * scale);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) == WHITE {
+                    out += &format!("<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"white\"/>\n",
+                                    x * scale, y * scale, s = scale);
+                }
+            }
+        }
+        out + "</svg>\n"
+    }
+
+    /// Writes the picture in the format given by the extension of `path`:
+    /// `ppm`, `png`, `svg` or `gif`.
+    pub fn save<P: AsRef<Path>>(&self, path: P, scale: usize) -> io::Result<()> {
+        let path = path.as_ref();
+        let data = match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => self.to_ppm(scale),
+            Some("png") => self.to_png(scale)?,
+            Some("svg") => self.to_svg(scale).into_bytes(),
+            Some("gif") => to_gif(std::slice::from_ref(self), scale, 0)?,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                           format!("{}: unknown image format, use ppm, png, svg or gif", path.display()))),
+        };
+        fs::write(path, data)
+    }
+}
+
+/// An animated GIF showing `frames` in order, each for `delay` hundredths of
+/// a second. All frames must have the same size, at most 65535 pixels wide
+/// and high once scaled.
+pub fn to_gif(frames: &[Picture], scale: usize, delay: u16) -> io::Result<Vec<u8>> {
+    let encoding = |e: gif::EncodingError| invalid(&e.to_string());
+    let (width, height) = frames.first().map_or((0, 0), |f| (f.width * scale, f.height * scale));
+    let size = |n: usize| u16::try_from(n).map_err(|_| invalid("picture too large for a GIF"));
+    let (width, height) = (size(width)?, size(height)?);
+    // the SIF digits index the palette
+    let palette = RGBA.iter().flat_map(|rgba| rgba[..3].to_vec()).collect::<Vec<_>>();
+    let mut encoder = gif::Encoder::new(Vec::new(), width, height, &palette).map_err(encoding)?;
+    if frames.len() > 1 {
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(encoding)?;
+    }
+    for frame in frames {
+        assert_eq!((frame.width * scale, frame.height * scale), (width as usize, height as usize), "GIF frames differ in size");
+        let indices = (0..height as usize)
+            .flat_map(|y| (0..width as usize).map(move |x| frame.get(x / scale, y / scale)))
+            .collect::<Vec<_>>();
+        encoder.write_frame(&gif::Frame {
+            width,
+            height,
+            delay,
+            dispose: gif::DisposalMethod::Background,
+            transparent: Some(TRANSPARENT),
+            buffer: indices.into(),
+            ..gif::Frame::default()
+        }).map_err(encoding)?;
+    }
+    encoder.into_inner()
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ppm() {
+        let picture = Picture::new(2, 1, vec![WHITE, BLACK]);
+        assert_eq!(picture.to_ppm(1), b"P6\n2 1\n255\n\xff\xff\xff\x00\x00\x00".to_vec());
+        assert_eq!(picture.to_ppm(3).len(), b"P6\n6 3\n255\n".len() + 6 * 3 * 3);
+    }
+
+    #[test]
+    fn test_png() {
+        let png = Picture::new(1, 1, vec![WHITE]).to_png(1).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"));
+        // a scaled up picture is mostly runs, which deflate squeezes well
+        let big = Picture::from_rows(&[vec![true, false], vec![false, true]]).to_png(100).unwrap();
+        assert!(big.len() < 200 * 200 * 4 / 10);
+        assert!(Picture::new(0, 0, Vec::new()).to_png(1).is_err());
+    }
+
+    #[test]
+    fn test_read() {
+        let picture = Picture::new(3, 2, vec![WHITE, BLACK, TRANSPARENT, BLACK, WHITE, WHITE]);
+        assert_eq!(Picture::from_png(&picture.to_png(1).unwrap()).unwrap(), picture);
+        let opaque = Picture::new(9, 2, vec![BLACK, WHITE, WHITE, WHITE, WHITE, WHITE, WHITE, WHITE, BLACK,
+                                             WHITE, BLACK, WHITE, WHITE, WHITE, WHITE, WHITE, WHITE, WHITE]);
+        assert_eq!(Picture::from_pbm(b"P1\n# comment\n9 2\n1 0 0 0 0 0 0 0 1\n010000000\n").unwrap(), opaque);
+        assert_eq!(Picture::from_pbm(b"P4 9 2\n\x80\x80\x40\x00").unwrap(), opaque);
+        assert!(Picture::from_pbm(b"P4 9 2\n\x80").is_err());
+        let huge = format!("P1 {0} {0}\n1", usize::MAX);
+        assert_eq!(Picture::from_pbm(huge.as_bytes()).unwrap_err().to_string(), "PBM too large");
+        assert_eq!(Picture::from_pbm(b"P1 100000 100000\n1").unwrap_err().to_string(), "PBM ends too early");
+    }
+
+    #[test]
+    fn test_gif() {
+        let frames = [Picture::new(2, 1, vec![WHITE, TRANSPARENT]), Picture::new(2, 1, vec![BLACK, WHITE])];
+        let data = to_gif(&frames, 2, 20).unwrap();
+        let mut decoder = gif::Decoder::new(&data[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (4, 2));
+        for frame in &frames {
+            let decoded = decoder.read_next_frame().unwrap().unwrap();
+            assert_eq!((decoded.delay, decoded.transparent), (20, Some(TRANSPARENT)));
+            let pixels = (0..2).flat_map(|_| frame.pixels.iter().flat_map(|&p| vec![p, p])).collect::<Vec<_>>();
+            assert_eq!(decoded.buffer.to_vec(), pixels);
+        }
+        assert!(decoder.read_next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_svg() {
+        let svg = Picture::from_rows(&[vec![true, false], vec![false, true]]).to_svg(10);
+        assert_eq!(svg.matches("fill=\"white\"").count(), 2);
+        assert!(svg.contains("<rect x=\"10\" y=\"10\" width=\"10\" height=\"10\" fill=\"white\"/>"));
+    }
+}
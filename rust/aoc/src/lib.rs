@@ -0,0 +1,8 @@
+//! Helpers shared between the days that are not about Intcode.
+
+pub mod grid;
+pub mod image;
+pub mod ocr;
+pub mod output;
+pub mod parse;
+pub mod rng;
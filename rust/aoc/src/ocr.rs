@@ -0,0 +1,153 @@
+//! Reading the block letters that day08 and day11 draw.
+//!
+//! The puzzles use a 6 pixel tall font where most capitals are 4 pixels
+//! wide, separated by one blank column. The picture is cropped to its lit
+//! area and split on blank columns; a run wider than any glyph (a 5 wide `Y`
+//! touching its neighbour) is split after a known glyph.
+
+use std::fmt;
+
+const HEIGHT: usize = 6;
+
+const FONT: &[(char, [&str; HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    Blank,
+    WrongHeight(usize),
+    /// A glyph that is not in the font, drawn with `#` and `.`.
+    UnknownGlyph { position: usize, glyph: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Blank => write!(f, "nothing to read, the picture is blank"),
+            OcrError::WrongHeight(h) => write!(f, "letters are {} pixels tall, expected {}", h, HEIGHT),
+            OcrError::UnknownGlyph { position, glyph } => write!(f, "unknown glyph at letter {}:\n{}", position + 1, glyph),
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the text in a picture given as rows of lit (`true`) pixels.
+pub fn recognize(rows: &[Vec<bool>]) -> Result<String, OcrError> {
+    let lit = |x: usize, y: usize| rows[y].get(x).copied().unwrap_or(false);
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let lit_rows = (0..rows.len()).filter(|&y| (0..width).any(|x| lit(x, y))).collect::<Vec<_>>();
+    let (top, bottom) = match (lit_rows.first(), lit_rows.last()) {
+        (Some(&top), Some(&bottom)) => (top, bottom),
+        _ => return Err(OcrError::Blank),
+    };
+    if bottom - top + 1 != HEIGHT {
+        return Err(OcrError::WrongHeight(bottom - top + 1));
+    }
+    let column = |x: usize| (top..=bottom).map(|y| lit(x, y)).collect::<Vec<_>>();
+    let columns = (0..width).map(column).collect::<Vec<_>>();
+
+    let mut text = String::new();
+    let mut x = 0;
+    while x < width {
+        if columns[x].iter().all(|&p| !p) {
+            x += 1;
+            continue;
+        }
+        let end = (x..width).find(|&i| columns[i].iter().all(|&p| !p)).unwrap_or(width);
+        let run = &columns[x..end];
+        match glyph_at(run) {
+            Some((letter, glyph_width)) => {
+                text.push(letter);
+                x += glyph_width;
+            }
+            None => return Err(OcrError::UnknownGlyph { position: text.len(), glyph: draw(run) }),
+        }
+    }
+    Ok(text)
+}
+
+/// Finds the glyph matching the whole run, or one at its start when the run
+/// is wider than any glyph.
+fn glyph_at(run: &[Vec<bool>]) -> Option<(char, usize)> {
+    let matches = |pattern: &[&str; HEIGHT], columns: &[Vec<bool>]| {
+        pattern[0].len() == columns.len() && (0..HEIGHT).all(|y| {
+            pattern[y].bytes().zip(columns).all(|(c, column)| (c == b'#') == column[y])
+        })
+    };
+    FONT.iter()
+        .find(|(_, pattern)| matches(pattern, run))
+        .or_else(|| FONT.iter().find(|(_, pattern)| {
+            pattern[0].len() < run.len() && matches(pattern, &run[..pattern[0].len()])
+        }))
+        .map(|(letter, pattern)| (*letter, pattern[0].len()))
+}
+
+fn draw(columns: &[Vec<bool>]) -> String {
+    (0..HEIGHT).map(|y| columns.iter().map(|c| if c[y] { '#' } else { '.' }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture(rows: &[&str]) -> Vec<Vec<bool>> {
+        rows.iter().map(|row| row.chars().map(|c| c == '#').collect()).collect()
+    }
+
+    #[test]
+    fn test_hull_style() {
+        let rows = picture(&[
+            "..........................",
+            "...###..####.#..#..##.....",
+            "...#..#.#....#..#.#..#....",
+            "...#..#.###..####.#.......",
+            "...###..#....#..#.#.##....",
+            "...#.#..#....#..#.#..#....",
+            "...#..#.####.#..#..###....",
+        ]);
+        assert_eq!(recognize(&rows), Ok("REHG".to_string()));
+    }
+
+    #[test]
+    fn test_touching_y() {
+        // day08 style, every letter in a 5 column cell
+        let rows = picture(&[
+            "#...##..#.",
+            "#...##..#.",
+            ".#.#.####.",
+            "..#..#..#.",
+            "..#..#..#.",
+            "..#..#..#.",
+        ]);
+        assert_eq!(recognize(&rows), Ok("YH".to_string()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(recognize(&picture(&["....", "...."])), Err(OcrError::Blank));
+        assert_eq!(recognize(&picture(&["#", "#"])), Err(OcrError::WrongHeight(2)));
+        let rows = picture(&["#.#", "#.#", "###", "#.#", "#.#", "###"]);
+        assert!(matches!(recognize(&rows), Err(OcrError::UnknownGlyph { position: 0, .. })));
+    }
+}
@@ -0,0 +1,207 @@
+//! Printing the answers, either as `Part N: …` lines or, with `--format
+//! json`, as one JSON object per line and part for other programs to read:
+//!
+//! ```text
+//! {"answer":296,"day":8,"part":1,"time_ns":41250}
+//! ```
+//!
+//! Picture answers give the picture as their answer, one `#`/`.` row per
+//! line, and the letters read from it as `ocr` (`null` if unreadable).
+//! Numbers that do not fit in 64 bits are given as strings.
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::ocr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    /// The digits of a number.
+    Number(String),
+    Text(String),
+    /// Rows of lit pixels and the letters they spell, if they could be read.
+    Picture { rows: Vec<Vec<bool>>, text: Option<String> },
+    /// No answer for this input, like day06 without `YOU` and `SAN`.
+    Missing,
+}
+
+impl Answer {
+    pub fn number<N: Display>(n: N) -> Answer {
+        Answer::Number(n.to_string())
+    }
+
+    pub fn picture(rows: Vec<Vec<bool>>) -> Answer {
+        let text = ocr::recognize(&rows).ok();
+        Answer::Picture { rows, text }
+    }
+}
+
+macro_rules! number_answers {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Answer {
+                Answer::number(n)
+            }
+        })*
+    };
+}
+
+number_answers!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(answer: Option<T>) -> Answer {
+        answer.map_or(Answer::Missing, Into::into)
+    }
+}
+
+/// Where a day's answers go.
+pub struct Output {
+    day: u32,
+    format: Format,
+    /// Time spent in `time` since the last answer.
+    shared: Cell<Duration>,
+}
+
+impl Output {
+    pub fn new(day: u32, format: Format) -> Output {
+        Output { day, format, shared: Cell::new(Duration::default()) }
+    }
+
+    /// Runs work that the answers are computed from, adding its time to
+    /// the next answer printed.
+    pub fn time<T>(&self, work: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = work();
+        self.shared.set(self.shared.get() + start.elapsed());
+        result
+    }
+
+    /// Prints the answer to `part`, timing how long `solve` takes.
+    pub fn part<A: Into<Answer>>(&self, part: u32, solve: impl FnOnce() -> A) {
+        self.print(Some(part), solve)
+    }
+
+    /// Prints an answer that is not one of the puzzle's parts, like day04's
+    /// count for other rules: bare in text and without a `part` in JSON.
+    pub fn other<A: Into<Answer>>(&self, solve: impl FnOnce() -> A) {
+        self.print(None, solve)
+    }
+
+    fn print<A: Into<Answer>>(&self, part: Option<u32>, solve: impl FnOnce() -> A) {
+        let start = Instant::now();
+        let answer = solve().into();
+        let time = self.shared.replace(Duration::default()) + start.elapsed();
+        print!("{}", self.format(part, &answer, time));
+    }
+
+    /// The lines printed for one answer.
+    pub fn format(&self, part: Option<u32>, answer: &Answer, time: Duration) -> String {
+        match self.format {
+            Format::Text => text(part, answer),
+            Format::Json => json(self.day, part, answer, time),
+        }
+    }
+}
+
+/// The first line of an answer, `value` is `None` when there is none.
+fn label(part: Option<u32>, value: Option<&str>) -> String {
+    match (part, value) {
+        (Some(part), Some(value)) => format!("Part {}: {}\n", part, value),
+        (Some(part), None) => format!("Part {}:\n", part),
+        (None, value) => format!("{}\n", value.unwrap_or("")),
+    }
+}
+
+fn text(part: Option<u32>, answer: &Answer) -> String {
+    match answer {
+        Answer::Number(text) | Answer::Text(text) => label(part, Some(text)),
+        Answer::Picture { rows, text } => {
+            let mut out = label(part, Some(text.as_deref().unwrap_or("")));
+            for row in rows {
+                out.extend(row.iter().map(|&lit| if lit { '█' } else { ' ' }));
+                out.push('\n');
+            }
+            out
+        }
+        Answer::Missing => label(part, None),
+    }
+}
+
+fn json(day: u32, part: Option<u32>, answer: &Answer, time: Duration) -> String {
+    let mut record = json!({ "day": day, "time_ns": time.as_nanos() as u64 });
+    if let Some(part) = part {
+        record["part"] = json!(part);
+    }
+    record["answer"] = match answer {
+        Answer::Number(digits) => digits.parse::<i64>().map(Value::from)
+            .or_else(|_| digits.parse::<u64>().map(Value::from))
+            .unwrap_or_else(|_| Value::from(digits.as_str())),
+        Answer::Text(text) => json!(text),
+        Answer::Picture { rows, text } => {
+            record["ocr"] = json!(text);
+            let picture = rows.iter()
+                .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect::<String>())
+                .collect::<Vec<_>>();
+            json!(picture.join("\n"))
+        }
+        Answer::Missing => Value::Null,
+    };
+    record.to_string() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text() {
+        let output = Output::new(6, Format::Text);
+        let time = Duration::from_micros(5);
+        assert_eq!(output.format(Some(1), &42_u64.into(), time), "Part 1: 42\n");
+        assert_eq!(output.format(Some(2), &None::<usize>.into(), time), "Part 2:\n");
+        let picture = Answer::picture(vec![vec![true, false], vec![false, true]]);
+        assert_eq!(output.format(Some(2), &picture, time), "Part 2: \n█ \n █\n");
+        assert_eq!(output.format(None, &Answer::number(1277), time), "1277\n");
+    }
+
+    #[test]
+    fn test_json() {
+        let output = Output::new(8, Format::Json);
+        let time = Duration::from_nanos(1500);
+        assert_eq!(output.format(Some(1), &Answer::number(296), time), "{\"answer\":296,\"day\":8,\"part\":1,\"time_ns\":1500}\n");
+        assert_eq!(output.format(Some(1), &Answer::number(u128::MAX), time),
+                   format!("{{\"answer\":\"{}\",\"day\":8,\"part\":1,\"time_ns\":1500}}\n", u128::MAX));
+        assert_eq!(output.format(Some(2), &Answer::Text("a \"b\"\\\t".to_string()), time),
+                   "{\"answer\":\"a \\\"b\\\"\\\\\\t\",\"day\":8,\"part\":2,\"time_ns\":1500}\n");
+        let picture = Answer::Picture { rows: vec![vec![true, false], vec![false, true]], text: Some("X".to_string()) };
+        assert_eq!(output.format(Some(2), &picture, time),
+                   "{\"answer\":\"#.\\n.#\",\"day\":8,\"ocr\":\"X\",\"part\":2,\"time_ns\":1500}\n");
+        assert_eq!(output.format(Some(2), &Answer::Missing, time), "{\"answer\":null,\"day\":8,\"part\":2,\"time_ns\":1500}\n");
+        assert_eq!(output.format(None, &Answer::number(7), time), "{\"answer\":7,\"day\":8,\"time_ns\":1500}\n");
+    }
+}
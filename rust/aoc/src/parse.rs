@@ -0,0 +1,138 @@
+//! Parsing puzzle inputs with errors that point at the offending text.
+//!
+//! Line and column numbers start at 1 and columns count characters. Every
+//! day reads its input through here and accepts `--check-input`, which only
+//! parses the input and reports whether it is valid.
+
+use std::fmt;
+use std::fmt::Display;
+use std::io;
+use std::io::prelude::*;
+use std::process;
+use std::str::FromStr;
+
+/// Longer source lines are cut down to this many characters around the
+/// error.
+const EXCERPT: usize = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// What was found where something else was expected.
+    pub text: String,
+    pub expected: String,
+    /// The whole line, shown under the message.
+    pub source: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, text: &str, expected: &str, source: &str) -> ParseError {
+        ParseError { line, column, text: text.to_string(), expected: expected.to_string(), source: source.to_string() }
+    }
+
+    /// An error at the character `offset` into `input`.
+    pub fn at_offset(input: &str, offset: usize, text: &str, expected: &str) -> ParseError {
+        let before = input.chars().take(offset).collect::<String>();
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        let source = input.lines().nth(line - 1).unwrap_or("");
+        ParseError::new(line, column, text, expected, source)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.text.is_empty() {
+            write!(f, "line {}, column {}: expected {}", self.line, self.column, self.expected)?;
+        } else {
+            write!(f, "line {}, column {}: expected {}, found `{}`", self.line, self.column, self.expected, self.text)?;
+        }
+        if !self.source.is_empty() {
+            let chars = self.source.chars().collect::<Vec<_>>();
+            let start = if chars.len() <= EXCERPT { 0 } else { (self.column - 1).saturating_sub(EXCERPT / 2).min(chars.len() - EXCERPT) };
+            let end = (start + EXCERPT).min(chars.len());
+            let prefix = if start > 0 { "..." } else { "" };
+            let suffix = if end < chars.len() { "..." } else { "" };
+            let excerpt = chars[start..end].iter().collect::<String>();
+            let width = self.text.chars().count().clamp(1, EXCERPT);
+            write!(f, "\n    {}{}{}\n    {}{}", prefix, excerpt, suffix,
+                   " ".repeat(prefix.len() + self.column - 1 - start), "^".repeat(width))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The lines of `input` with something on them, with their line numbers.
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.lines().enumerate().map(|(i, line)| (i + 1, line)).filter(|(_, line)| !line.trim().is_empty())
+}
+
+/// Splits `line` at every `separator`, giving each field without the
+/// whitespace around it, and the column it starts at.
+pub fn fields(line: &str, separator: char) -> impl Iterator<Item = (usize, &str)> {
+    let mut column = 1;
+    line.split(separator).map(move |field| {
+        let start = column + field.chars().take_while(|c| c.is_whitespace()).count();
+        column += field.chars().count() + 1;
+        (start, field.trim())
+    })
+}
+
+/// Parses `text`, found at `line` and `column` of `source`, as a number.
+pub fn number<T: FromStr>(line: usize, column: usize, text: &str, source: &str) -> Result<T, ParseError> {
+    text.parse().map_err(|_| ParseError::new(line, column, text, "a number", source))
+}
+
+/// All of stdin, exiting if it can not be read.
+pub fn read_stdin() -> String {
+    let mut input = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut input) {
+        eprintln!("cannot read input: {}", e);
+        process::exit(1);
+    }
+    input
+}
+
+/// The parsed input, or exits after printing why it could not be parsed.
+pub fn or_exit<T, E: Display>(parsed: Result<T, E>) -> T {
+    parsed.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1)
+    })
+}
+
+/// What `--check-input` does: reports whether the input parsed and exits.
+pub fn check<T, E: Display>(parsed: Result<T, E>) -> ! {
+    or_exit(parsed);
+    println!("input ok");
+    process::exit(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        let line = "R8, U5,,L5";
+        assert_eq!(fields(line, ',').collect::<Vec<_>>(), vec![(1, "R8"), (5, "U5"), (8, ""), (9, "L5")]);
+        assert_eq!(lines("a\n\n  \nb\n").collect::<Vec<_>>(), vec![(1, "a"), (4, "b")]);
+        assert_eq!(number::<u32>(4, 5, "U5", line), Err(ParseError::new(4, 5, "U5", "a number", line)));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::at_offset("123\n45x6\n", 6, "x", "a digit");
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.to_string(), "line 2, column 3: expected a digit, found `x`\n    45x6\n      ^");
+        let error = ParseError::new(1, 1, "", "a mass", "");
+        assert_eq!(error.to_string(), "line 1, column 1: expected a mass");
+        let source = "0".repeat(100) + "x" + &"1".repeat(100);
+        let error = ParseError::at_offset(&source, 100, "x", "a digit");
+        let excerpt = format!("...{}x{}...", "0".repeat(30), "1".repeat(29));
+        assert_eq!(error.to_string(), format!("line 1, column 101: expected a digit, found `x`\n    {}\n    {}^", excerpt, " ".repeat(33)));
+    }
+}
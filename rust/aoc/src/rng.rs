@@ -0,0 +1,62 @@
+//! A small seeded random number generator (xorshift64*) for making up
+//! puzzle inputs. Not for anything that needs real randomness.
+
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        // the state must never be zero
+        XorShift((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..n`, every one equally likely: draws that would make
+    /// the remainder favour the smaller numbers are drawn again.
+    pub fn below(&mut self, n: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let draw = |seed| {
+            let mut rng = XorShift::new(seed);
+            (0..100).map(|_| rng.below(6)).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(1), draw(1));
+        assert_ne!(draw(1), draw(2));
+        assert!(draw(0).iter().all(|&n| n < 6));
+        let mut rng = XorShift::new(3);
+        assert!(!rng.chance(0.0) && rng.chance(1.0));
+    }
+
+    #[test]
+    fn test_uniform() {
+        // a plain remainder would put half of these in the lowest third
+        let mut rng = XorShift::new(4);
+        let n = 3 << 62;
+        let low = (0..3000).filter(|_| rng.below(n) < 1 << 62).count();
+        assert!((850..1150).contains(&low), "{} of 3000 in the lowest third", low);
+    }
+}
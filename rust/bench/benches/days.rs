@@ -0,0 +1,150 @@
+//! Parsing and both parts of every day, on the inputs stored in `inputs/`.
+//!
+//! A puzzle input saved as `inputs/dayNN/input.txt` is used instead of the
+//! stored one when it exists, and `generate` makes one of any size:
+//!
+//!     cargo run -p generate -- day06 --bodies 100000 > inputs/day06/input.txt
+//!
+//! Compare runs with
+//!
+//!     cargo bench -p bench --bench days -- --save-baseline before
+//!     cargo bench -p bench --bench days
+//!     cargo run -p bench -- before
+
+use std::fs;
+use std::path::Path;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use num_bigint::BigUint;
+
+use aoc::ocr;
+use day01::fuel;
+use day02::gravity;
+use day03::wires;
+use day04::count::{self, Counter};
+use day04::rules::{self, PART1, PART2};
+use day05::diagnostic;
+use day06::orbits::OrbitMap;
+use day07::amplifiers;
+use day08::sif::SifImage;
+use day09::boost;
+use day11::robot;
+use intcode::loader;
+
+/// The puzzle input of `day` if there is one, `stored` otherwise.
+fn input(day: u32, stored: &str) -> String {
+    let inputs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../inputs").join(format!("day{:02}", day));
+    let path = Some(inputs.join("input.txt")).filter(|p| p.is_file()).unwrap_or_else(|| inputs.join(stored));
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+fn day01(c: &mut Criterion) {
+    let text = input(1, "example.txt");
+    let masses = fuel::parse(&text).unwrap();
+    let mut group = c.benchmark_group("day01");
+    group.bench_function("parse", |b| b.iter(|| fuel::parse(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| black_box(&masses).iter().map(fuel::direct).sum::<BigUint>()));
+    group.bench_function("part2", |b| b.iter(|| black_box(&masses).iter().map(fuel::total).sum::<BigUint>()));
+    group.finish();
+}
+
+fn day02(c: &mut Criterion) {
+    let text = input(2, "crafted.txt");
+    let memory = loader::load_cells::<usize, _>(text.as_bytes(), "a cell that is not negative").unwrap();
+    let mut group = c.benchmark_group("day02");
+    group.bench_function("parse", |b| b.iter(|| loader::parse_text(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| gravity::part1(black_box(&memory))));
+    group.bench_function("part2", |b| b.iter(|| gravity::part2(black_box(&memory))));
+    group.finish();
+}
+
+fn day03(c: &mut Criterion) {
+    let text = input(3, "example3.txt");
+    let found = wires::parse(&text).unwrap();
+    let mut group = c.benchmark_group("day03");
+    group.bench_function("parse", |b| b.iter(|| wires::parse(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| wires::crossings(black_box(&found))[0].distance));
+    group.bench_function("part2", |b| b.iter(|| wires::crossings(black_box(&found)).iter().map(|c| c.steps).min()));
+    group.finish();
+}
+
+fn day04(c: &mut Criterion) {
+    let (low, high) = count::range("138241-674034").unwrap();
+    let (part1, part2) = (rules::parse(PART1).unwrap(), rules::parse(PART2).unwrap());
+    let mut group = c.benchmark_group("day04");
+    group.bench_function("parse", |b| b.iter(|| rules::parse(black_box(PART2)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| Counter::new(&*part1).count(black_box(low), black_box(high))));
+    group.bench_function("part2", |b| b.iter(|| Counter::new(&*part2).count(black_box(low), black_box(high))));
+    group.finish();
+}
+
+fn day05(c: &mut Criterion) {
+    let text = input(5, "compare.txt");
+    let memory = loader::load_cells::<isize, _>(text.as_bytes(), "a cell that fits an isize").unwrap();
+    let mut group = c.benchmark_group("day05");
+    group.bench_function("parse", |b| b.iter(|| loader::parse_text(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| diagnostic::part1(black_box(&memory))));
+    group.bench_function("part2", |b| b.iter(|| diagnostic::part2(black_box(&memory))));
+    group.finish();
+}
+
+fn day06(c: &mut Criterion) {
+    let text = input(6, "example.txt");
+    let map = OrbitMap::parse(&text).unwrap();
+    let (you, san) = (map.id("YOU").unwrap(), map.id("SAN").unwrap());
+    let mut group = c.benchmark_group("day06");
+    group.bench_function("parse", |b| b.iter(|| OrbitMap::parse(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| black_box(&map).total_orbits()));
+    group.bench_function("part2", |b| b.iter(|| black_box(&map).transfers(you, san)));
+    group.finish();
+}
+
+fn day07(c: &mut Criterion) {
+    let text = input(7, "feedback.txt");
+    let memory = loader::load_cells::<i128, _>(text.as_bytes(), "a number").unwrap();
+    let mut group = c.benchmark_group("day07");
+    group.bench_function("parse", |b| b.iter(|| loader::parse_text(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| amplifiers::part1(black_box(&memory))));
+    group.bench_function("part2", |b| b.iter(|| amplifiers::part2(black_box(&memory))));
+    group.finish();
+}
+
+fn day08(c: &mut Criterion) {
+    let text = input(8, "hello.txt");
+    let image = SifImage::decode(25, 6, &text).unwrap();
+    let read = |image: &SifImage| {
+        let screen = image.composite();
+        let rows = screen.rows().map(|row| row.iter().map(|&p| p == aoc::image::WHITE).collect()).collect::<Vec<_>>();
+        ocr::recognize(&rows)
+    };
+    let mut group = c.benchmark_group("day08");
+    group.bench_function("parse", |b| b.iter(|| SifImage::decode(25, 6, black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| black_box(&image).checksum()));
+    group.bench_function("part2", |b| b.iter(|| read(black_box(&image))));
+    group.finish();
+}
+
+fn day09(c: &mut Criterion) {
+    let text = input(9, "quine.txt");
+    let memory = loader::parse_text(&text).unwrap();
+    let mut group = c.benchmark_group("day09");
+    group.bench_function("parse", |b| b.iter(|| loader::parse_text(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| boost::part1(black_box(&memory))));
+    group.bench_function("part2", |b| b.iter(|| boost::part2(black_box(&memory))));
+    group.finish();
+}
+
+fn day11(c: &mut Criterion) {
+    let text = input(11, "painter.txt");
+    let memory = loader::parse_text(&text).unwrap();
+    let mut group = c.benchmark_group("day11");
+    group.bench_function("parse", |b| b.iter(|| loader::parse_text(black_box(&text)).unwrap()));
+    group.bench_function("part1", |b| b.iter(|| robot::paint(black_box(&memory), 0, None, None).len()));
+    group.bench_function("part2", |b| {
+        b.iter(|| ocr::recognize(&robot::lit_rows(&robot::paint(black_box(&memory), 1, None, None))))
+    });
+    group.finish();
+}
+
+criterion_group!(days, day01, day02, day03, day04, day05, day06, day07, day08, day09, day11);
+criterion_main!(days);
@@ -0,0 +1,100 @@
+//! Comparing two runs of the benchmarks saved by Criterion.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+/// A benchmark's mean time per iteration, with the bounds of its
+/// confidence interval, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub mean: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Reads the mean out of a Criterion `estimates.json`.
+pub fn estimate(json: &str) -> Option<Estimate> {
+    let estimates = serde_json::from_str::<Value>(json).ok()?;
+    let mean = estimates.get("mean")?;
+    let interval = mean.get("confidence_interval")?;
+    Some(Estimate {
+        mean: mean.get("point_estimate")?.as_f64()?,
+        low: interval.get("lower_bound")?.as_f64()?,
+        high: interval.get("upper_bound")?.as_f64()?,
+    })
+}
+
+/// A time in nanoseconds with the unit that suits it.
+pub fn duration(ns: f64) -> String {
+    match ns {
+        ns if ns < 1e3 => format!("{:.1} ns", ns),
+        ns if ns < 1e6 => format!("{:.2} µs", ns / 1e3),
+        ns if ns < 1e9 => format!("{:.2} ms", ns / 1e6),
+        ns => format!("{:.2} s", ns / 1e9),
+    }
+}
+
+/// One row per benchmark in either run. A change is only called faster or
+/// slower when the confidence intervals do not overlap.
+pub fn report(before: &[(String, Estimate)], after: &[(String, Estimate)]) -> String {
+    let mut names = before.iter().chain(after).map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    let find = |run: &[(String, Estimate)], name: &str| run.iter().find(|(n, _)| n == name).map(|&(_, e)| e);
+
+    let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0).max("benchmark".len());
+    let mut out = String::new();
+    writeln!(out, "{:<w$}  {:>10}  {:>10}  {:>8}", "benchmark", "before", "after", "change", w = width).unwrap();
+    for name in names {
+        let (old, new) = (find(before, name), find(after, name));
+        let time = |e: Option<Estimate>| e.map(|e| duration(e.mean)).unwrap_or_else(|| "-".to_string());
+        let change = match (old, new) {
+            (Some(old), Some(new)) => {
+                let percent = (new.mean / old.mean - 1.0) * 100.0;
+                let verdict = if new.high < old.low {
+                    "  faster"
+                } else if new.low > old.high {
+                    "  slower"
+                } else {
+                    ""
+                };
+                format!("{:>+7.1}%{}", percent, verdict)
+            }
+            _ => format!("{:>8}", "-"),
+        };
+        writeln!(out, "{:<w$}  {:>10}  {:>10}  {}", name, time(old), time(new), change, w = width).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(mean: f64) -> Estimate {
+        Estimate { mean, low: mean * 0.95, high: mean * 1.05 }
+    }
+
+    #[test]
+    fn test_estimate() {
+        let json = r#"{"mean":{"confidence_interval":{"confidence_level":0.95,"lower_bound":90.5,"upper_bound":110.0},
+                       "point_estimate":100.25,"standard_error":5.0},"median":{}}"#;
+        assert_eq!(estimate(json), Some(Estimate { mean: 100.25, low: 90.5, high: 110.0 }));
+        assert_eq!(estimate("{}"), None);
+        assert_eq!((duration(999.0), duration(1500.0), duration(2.5e6), duration(3e9)),
+                   ("999.0 ns".to_string(), "1.50 µs".to_string(), "2.50 ms".to_string(), "3.00 s".to_string()));
+    }
+
+    #[test]
+    fn test_report() {
+        let before = vec![("day01/part1".to_string(), at(100.0)), ("day07/part2".to_string(), at(2e6)), ("old".to_string(), at(5.0))];
+        let after = vec![("day01/part1".to_string(), at(102.0)), ("day07/part2".to_string(), at(1e6))];
+        assert_eq!(report(&before, &after), "\
+benchmark        before       after    change
+day01/part1    100.0 ns    102.0 ns     +2.0%
+day07/part2     2.00 ms     1.00 ms    -50.0%  faster
+old              5.0 ns           -         -
+");
+    }
+}
@@ -0,0 +1,3 @@
+//! Benchmarks of every day, and a report comparing two of their runs.
+
+pub mod compare;
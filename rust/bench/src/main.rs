@@ -0,0 +1,63 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use bench::compare::{self, Estimate};
+
+fn usage() -> ! {
+    eprintln!("usage: bench [--criterion DIR] BASELINE [NEW]");
+    eprintln!("compares the benchmarks saved as BASELINE with NEW, the last run by default");
+    process::exit(2)
+}
+
+/// Every benchmark's estimate saved under `baseline`, by name.
+fn estimates(criterion: &Path, baseline: &str) -> Vec<(String, Estimate)> {
+    let mut found = Vec::new();
+    let mut dirs = vec![criterion.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()) {
+            let file = path.join(baseline).join("estimates.json");
+            match fs::read_to_string(&file).ok().and_then(|json| compare::estimate(&json)) {
+                Some(estimate) => {
+                    let name = path.strip_prefix(criterion).unwrap().to_string_lossy().replace('\\', "/");
+                    found.push((name, estimate));
+                }
+                None => dirs.push(path),
+            }
+        }
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+}
+
+fn main() {
+    let target = env::var_os("CARGO_TARGET_DIR").map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../target"));
+    let mut criterion = target.join("criterion");
+    let mut names = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--criterion" => criterion = PathBuf::from(args.next().unwrap_or_else(|| usage())),
+            _ if arg.starts_with('-') => usage(),
+            _ => names.push(arg),
+        }
+    }
+    let (baseline, new) = match &names[..] {
+        [baseline] => (baseline.as_str(), "new"),
+        [baseline, new] => (baseline.as_str(), new.as_str()),
+        _ => usage(),
+    };
+
+    let before = estimates(&criterion, baseline);
+    if before.is_empty() {
+        eprintln!("no benchmarks saved as {} in {}", baseline, criterion.display());
+        process::exit(1);
+    }
+    print!("{}", compare::report(&before, &estimates(&criterion, new)));
+}
@@ -0,0 +1,143 @@
+//! Fuel for the spacecraft modules.
+//!
+//! Fuel is a third of the mass rounded down, minus two, and never negative,
+//! so modules lighter than 9 need none. Masses are arbitrary precision.
+
+use std::fmt::Write;
+
+use aoc::parse::{self, ParseError};
+use num_bigint::BigUint;
+
+/// Fuel for `mass`, not counting the fuel needed for the fuel itself.
+pub fn direct(mass: &BigUint) -> BigUint {
+    let third = mass / 3_u32;
+    if third > BigUint::from(2_u32) { third - 2_u32 } else { BigUint::default() }
+}
+
+/// The fuel for `mass`, then the fuel for that fuel and so on, until no
+/// more is needed.
+pub fn chain(mass: &BigUint) -> Vec<BigUint> {
+    let mut chain = Vec::new();
+    let mut fuel = direct(mass);
+    while fuel != BigUint::default() {
+        let next = direct(&fuel);
+        chain.push(fuel);
+        fuel = next;
+    }
+    chain
+}
+
+/// All the fuel a module of `mass` needs, including fuel for the fuel.
+pub fn total(mass: &BigUint) -> BigUint {
+    chain(mass).into_iter().sum()
+}
+
+/// One mass per line, blank lines are skipped.
+pub fn parse(input: &str) -> Result<Vec<BigUint>, ParseError> {
+    parse::lines(input).map(|(line, text)| {
+        let column = text.chars().take_while(|c| c.is_whitespace()).count() + 1;
+        text.trim().parse().map_err(|_| ParseError::new(line, column, text.trim(), "a mass", text))
+    }).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub mass: BigUint,
+    /// `chain[0]` is the direct fuel, the rest is fuel for fuel.
+    pub chain: Vec<BigUint>,
+}
+
+impl Module {
+    pub fn new(mass: BigUint) -> Module {
+        let chain = chain(&mass);
+        Module { mass, chain }
+    }
+
+    pub fn direct(&self) -> BigUint {
+        self.chain.first().cloned().unwrap_or_default()
+    }
+
+    pub fn total(&self) -> BigUint {
+        self.chain.iter().sum()
+    }
+}
+
+fn join(fuel: &[BigUint], separator: &str) -> String {
+    fuel.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(separator)
+}
+
+/// A table with one row per module and the totals at the bottom.
+pub fn table(modules: &[Module]) -> String {
+    let mut rows = vec![["module".to_string(), "mass".to_string(), "fuel".to_string(), "total".to_string(), "fuel for fuel".to_string()]];
+    for (i, m) in modules.iter().enumerate() {
+        let rest = m.chain.get(1..).unwrap_or(&[]);
+        rows.push([(i + 1).to_string(), m.mass.to_string(), m.direct().to_string(), m.total().to_string(), join(rest, " + ")]);
+    }
+    let direct = modules.iter().map(Module::direct).sum::<BigUint>();
+    let total = modules.iter().map(Module::total).sum::<BigUint>();
+    rows.push(["all".to_string(), modules.iter().map(|m| &m.mass).sum::<BigUint>().to_string(),
+               direct.to_string(), total.to_string(), String::new()]);
+
+    let widths = (0..4).map(|c| rows.iter().map(|r| r[c].len()).max().unwrap()).collect::<Vec<_>>();
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        if i == 1 || i == rows.len() - 1 {
+            writeln!(out, "{}", widths.iter().map(|&w| "-".repeat(w)).collect::<Vec<_>>().join("  ")).unwrap();
+        }
+        let line = (0..4).map(|c| format!("{:>w$}", row[c], w = widths[c])).collect::<Vec<_>>().join("  ");
+        writeln!(out, "{}  {}", line, row[4]).unwrap();
+    }
+    out.lines().map(|l| l.trim_end().to_string() + "\n").collect()
+}
+
+/// The same as `table` as CSV, with the fuel chain in one space separated
+/// column.
+pub fn csv(modules: &[Module]) -> String {
+    let mut out = String::from("module,mass,fuel,total,chain\n");
+    for (i, m) in modules.iter().enumerate() {
+        writeln!(out, "{},{},{},{},{}", i + 1, m.mass, m.direct(), m.total(), join(&m.chain, " ")).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    #[test]
+    fn test_fuel() {
+        for &(mass, fuel) in &[(0, 0), (2, 0), (8, 0), (9, 1), (12, 2), (14, 2), (1969, 654), (100_756, 33583)] {
+            assert_eq!(direct(&big(mass)), big(fuel), "{}", mass);
+        }
+        assert_eq!(total(&big(14)), big(2));
+        assert_eq!(total(&big(1969)), big(966));
+        assert_eq!(total(&big(100_756)), big(50346));
+        assert_eq!(chain(&big(1969)), [654, 216, 70, 21, 5].iter().map(|&n| big(n)).collect::<Vec<_>>());
+        let huge = "1".repeat(60).parse::<BigUint>().unwrap();
+        assert!(total(&huge) > direct(&huge) && total(&huge) < &huge / 2_u32);
+    }
+
+    #[test]
+    fn test_reports() {
+        let modules = vec![Module::new(big(1969)), Module::new(big(5))];
+        assert_eq!(table(&modules), "\
+module  mass  fuel  total  fuel for fuel
+------  ----  ----  -----
+     1  1969   654    966  216 + 70 + 21 + 5
+     2     5     0      0
+------  ----  ----  -----
+   all  1974   654    966
+");
+        assert_eq!(csv(&modules), "module,mass,fuel,total,chain\n1,1969,654,966,654 216 70 21 5\n2,5,0,0,\n");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("12\n\n 1969\n").unwrap(), vec![big(12), big(1969)]);
+        assert_eq!(parse("12\n  -4 \n").unwrap_err(), ParseError::new(2, 3, "-4", "a mass", "  -4 "));
+    }
+}
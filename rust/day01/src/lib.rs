@@ -0,0 +1,3 @@
+//! The Tyranny of the Rocket Equation, day 1.
+
+pub mod fuel;
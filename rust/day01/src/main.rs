@@ -1,15 +1,47 @@
-use std::io;
-use std::io::prelude::*;
-use std::iter::successors;
+use std::env;
+use std::process;
+
+use aoc::output::{Answer, Format, Output};
+use aoc::parse;
+use num_bigint::BigUint;
+
+use day01::fuel::{self, Module};
+
+fn usage() -> ! {
+    eprintln!("usage: day01 [--report table|csv | --check-input] [--format text|json] < input");
+    process::exit(2)
+}
 
 fn main() {
-    let stdin = io::stdin();
-    let modules = stdin.lock().lines().map(|line| line.unwrap().parse().unwrap()).collect::<Vec<usize>>();
+    let mut report = None;
+    let mut check = false;
+    let mut format = Format::Text;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--check-input" => check = true,
+            "--report" => report = Some(args.next().filter(|f| f == "table" || f == "csv").unwrap_or_else(|| usage())),
+            "--format" => format = args.next().and_then(|f| Format::parse(&f)).unwrap_or_else(|| usage()),
+            _ => usage(),
+        }
+    }
+    if check && report.is_some() {
+        usage();
+    }
+
+    let input = parse::read_stdin();
+    if check {
+        parse::check(fuel::parse(&input));
+    }
+    let masses = parse::or_exit(fuel::parse(&input));
 
-    let part1: usize = modules.iter().map(|m| m / 3 - 2 ).sum();
-    println!("Part 1: {}", part1);
+    if let Some(format) = report {
+        let modules = masses.into_iter().map(Module::new).collect::<Vec<_>>();
+        print!("{}", if format == "csv" { fuel::csv(&modules) } else { fuel::table(&modules) });
+        return;
+    }
 
-    let fuelalltheway = |mass: &usize| successors(Some(*mass), |m| (m / 3).checked_sub(2)).skip(1).sum::<usize>();
-    let part2: usize = modules.iter().map(fuelalltheway).sum();
-    println!("Part 2: {}", part2);
+    let output = Output::new(1, format);
+    output.part(1, || Answer::number(masses.iter().map(fuel::direct).sum::<BigUint>()));
+    output.part(2, || Answer::number(masses.iter().map(fuel::total).sum::<BigUint>()));
 }
@@ -0,0 +1,48 @@
+//! Running the gravity assist program.
+
+/// Runs the program with `noun` and `verb` in addresses 1 and 2 and gives
+/// what it leaves in address 0.
+pub fn calc(a: usize, b: usize, mut memory: Vec<usize>) -> usize {
+    let mut pc = 0;
+    memory[1] = a;
+    memory[2] = b;
+    loop {
+        match memory[pc] {
+            1 => {
+                let a = memory[pc + 1];
+                let b = memory[pc + 2];
+                let out = memory[pc + 3];
+                memory[out] = memory[a] + memory[b];
+                pc += 4;
+            }
+            2 => {
+                let a = memory[pc + 1];
+                let b = memory[pc + 2];
+                let out = memory[pc + 3];
+                memory[out] = memory[a] * memory[b];
+                pc += 4;
+            }
+            99 => {
+                break;
+            }
+            _ => panic!("unknown opcode")
+        }
+    }
+    memory[0]
+}
+
+pub fn part1(memory: &[usize]) -> usize {
+    calc(12, 2, memory.to_vec())
+}
+
+/// `100 * noun + verb` for the inputs that make the program give 19690720.
+pub fn part2(memory: &[usize]) -> Option<usize> {
+    for a in 0..=99 {
+        for b in 0..=99 {
+            if calc(a, b, memory.to_vec()) == 19690720 {
+                return Some(100 * a + b);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,3 @@
+//! The 1202 Program Alarm of day 2.
+
+pub mod gravity;
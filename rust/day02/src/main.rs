@@ -1,47 +1,37 @@
+use std::env;
 use std::io;
-use std::io::prelude::*;
+use std::process;
 
-fn calc(a: usize, b: usize, mut memory: Vec<usize>) -> usize {
-    let mut pc = 0;
-    memory[1] = a;
-    memory[2] = b;
-    loop {
-        match memory[pc] {
-            1 => {
-                let a = memory[pc + 1];
-                let b = memory[pc + 2];
-                let out = memory[pc + 3];
-                memory[out] = memory[a] + memory[b];
-                pc += 4;
-            }
-            2 => {
-                let a = memory[pc + 1];
-                let b = memory[pc + 2];
-                let out = memory[pc + 3];
-                memory[out] = memory[a] * memory[b];
-                pc += 4;
-            }
-            99 => {
-                break;
-            }
-            _ => panic!("unknown opcode")
-        }
-    }
-    memory[0]
+use aoc::output::{Format, Output};
+use day02::gravity;
+use intcode::loader;
+
+fn usage() -> ! {
+    eprintln!("usage: day02 [--check-input] [--format text|json] < program");
+    process::exit(2)
 }
 
 fn main() {
-    let stdin = io::stdin();
-    let input = stdin.lock().lines().next().unwrap().unwrap().split(',').map(|n| n.parse().unwrap()).collect::<Vec<usize>>();
-
-    println!("Part 1: {}", calc(12, 2, input.to_vec()));
-
-    'mainloop: for a in 0..=99 {
-        for b in 0..=99 {
-            if calc(a,b, input.to_vec()) == 19690720 {
-                println!("Part 2: {}", 100 * a + b);
-                break 'mainloop;
-            }
+    let mut check = false;
+    let mut format = Format::Text;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--check-input" => check = true,
+            "--format" => format = args.next().and_then(|f| Format::parse(&f)).unwrap_or_else(|| usage()),
+            _ => usage(),
         }
     }
+    let input = loader::load_cells::<usize, _>(io::stdin(), "a cell that is not negative").unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1)
+    });
+    if check {
+        println!("input ok");
+        return;
+    }
+
+    let output = Output::new(2, format);
+    output.part(1, || gravity::part1(&input));
+    output.part(2, || gravity::part2(&input));
 }
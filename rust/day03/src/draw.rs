@@ -0,0 +1,138 @@
+//! Drawing the wires: an SVG of the whole panel and a downscaled character
+//! view for the terminal. Both mark every crossing, with the one closest to
+//! the origin and the one with the fewest combined steps highlighted.
+
+use std::fmt::Write;
+
+use aoc::grid::{Bounds, Point};
+
+use crate::wires::{Crossing, Wire};
+
+const COLOURS: &[&str] = &["#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2", "#17becf", "#bcbd22"];
+
+/// The crossings to highlight: closest to the origin, then fewest steps.
+fn best(crossings: &[Crossing]) -> (Option<Point>, Option<Point>) {
+    (crossings.iter().min_by_key(|c| c.distance).map(|c| c.point),
+     crossings.iter().min_by_key(|c| c.steps).map(|c| c.point))
+}
+
+/// The area covered by all wires, including the origin.
+fn bounds(wires: &[Wire]) -> Bounds {
+    wires.iter().flat_map(|w| &w.segments).fold(Bounds::new(Point::ORIGIN), |b, s| b.include(s.to))
+}
+
+/// One polyline per wire. The grid's y already points down like the SVG's,
+/// so `U` is drawn upwards as in the puzzle; titles use the puzzle's y.
+pub fn svg(wires: &[Wire], crossings: &[Crossing]) -> String {
+    let Bounds { min: Point { x: x0, y: y0 }, max: Point { x: x1, y: y1 } } = bounds(wires);
+    let margin = ((x1 - x0).max(y1 - y0) / 50).max(1);
+    let (closest, fewest) = best(crossings);
+    let radius = margin as f64 / 2.0;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n\
+         <rect x=\"{0}\" y=\"{1}\" width=\"{2}\" height=\"{3}\" fill=\"white\"/>\n",
+        x0 - margin, y0 - margin, x1 - x0 + 2 * margin, y1 - y0 + 2 * margin);
+    for (i, wire) in wires.iter().enumerate() {
+        let points = wire.segments.first().map(|s| s.from).into_iter()
+            .chain(wire.segments.iter().map(|s| s.to))
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        writeln!(out, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1\" \
+                       vector-effect=\"non-scaling-stroke\"><title>wire {}</title></polyline>",
+                 points.join(" "), COLOURS[i % COLOURS.len()], i + 1).unwrap();
+    }
+    for c in crossings {
+        writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"black\" vector-effect=\"non-scaling-stroke\">\
+                       <title>{},{}: distance {}, {} steps</title></circle>",
+                 c.point.x, c.point.y, radius, c.point.x, -c.point.y, c.distance, c.steps).unwrap();
+    }
+    for (point, colour, title) in &[(fewest, "blue", "fewest steps"), (closest, "red", "closest")] {
+        if let Some(p) = point {
+            writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"><title>{}</title></circle>",
+                     p.x, p.y, radius * 2.0, colour, title).unwrap();
+        }
+    }
+    writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{1}\" fill=\"black\"><title>origin</title></rect>",
+             -radius, -radius, radius * 2.0).unwrap();
+    out + "</svg>\n"
+}
+
+/// The wires squeezed into `columns` by `rows` characters. Wires are drawn
+/// with their number, `+` where several share a character, `x` at crossings,
+/// `C` at the closest one, `S` at the one with the fewest steps and `o` at
+/// the origin.
+pub fn terminal(wires: &[Wire], crossings: &[Crossing], columns: usize, rows: usize) -> String {
+    let Bounds { min: Point { x: x0, y: y0 }, max: Point { x: x1, y: y1 } } = bounds(wires);
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    let cell = |Point { x, y }: Point| {
+        let column = ((x - x0) as i128 * (columns - 1) as i128 / (x1 - x0).max(1) as i128) as usize;
+        let row = ((y - y0) as i128 * (rows - 1) as i128 / (y1 - y0).max(1) as i128) as usize;
+        (column, row)
+    };
+    let mut screen = vec![vec![' '; columns]; rows];
+    for (i, wire) in wires.iter().enumerate() {
+        let mark = std::char::from_digit((i as u32 + 1) % 36, 36).unwrap();
+        let mut drawn = vec![vec![false; columns]; rows];
+        for s in &wire.segments {
+            let (a, b) = (cell(s.from), cell(s.to));
+            for line in &mut drawn[a.1.min(b.1)..=a.1.max(b.1)] {
+                for d in &mut line[a.0.min(b.0)..=a.0.max(b.0)] {
+                    *d = true;
+                }
+            }
+        }
+        for (row, line) in drawn.iter().enumerate() {
+            for (column, _) in line.iter().enumerate().filter(|(_, &d)| d) {
+                let c = &mut screen[row][column];
+                *c = if *c == ' ' { mark } else { '+' };
+            }
+        }
+    }
+    let (closest, fewest) = best(crossings);
+    let marks = crossings.iter().map(|c| (c.point, 'x'))
+        .chain(fewest.map(|p| (p, 'S')))
+        .chain(closest.map(|p| (p, 'C')))
+        .chain(Some((Point::ORIGIN, 'o')));
+    for (point, mark) in marks {
+        let (column, row) = cell(point);
+        screen[row][column] = mark;
+    }
+    screen.into_iter().map(|line| line.into_iter().collect::<String>().trim_end().to_string() + "\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wires;
+
+    fn example() -> (Vec<Wire>, Vec<Crossing>) {
+        let wires = wires::parse("R8,U5,L5,D3\nU7,R6,D4,L4\n").unwrap();
+        let crossings = wires::crossings(&wires);
+        (wires, crossings)
+    }
+
+    #[test]
+    fn test_terminal() {
+        let (wires, crossings) = example();
+        let view = terminal(&wires, &crossings, 9, 8);
+        assert_eq!(view, "\
+            2222222\n\
+            2     2\n\
+            2  111S11\n\
+            2  1  2 1\n\
+            2 2C222 1\n\
+            2  1    1\n\
+            2       1\n\
+            o11111111\n");
+    }
+
+    #[test]
+    fn test_svg() {
+        let (wires, crossings) = example();
+        let svg = svg(&wires, &crossings);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains("points=\"0,0 8,0 8,-5 3,-5 3,-2\""));
+        assert!(svg.contains("<circle cx=\"3\" cy=\"-3\" r=\"1\" fill=\"red\">"));
+        assert!(svg.contains("<circle cx=\"6\" cy=\"-5\" r=\"1\" fill=\"blue\">"));
+    }
+}
@@ -0,0 +1,4 @@
+//! Crossed Wires of day 3.
+
+pub mod draw;
+pub mod wires;
@@ -1,53 +1,78 @@
-use std::io;
-use std::io::prelude::*;
-use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process;
 
-fn read_wire(wire: &Vec<String>) -> HashMap<(isize, isize), (u8, usize)> {
-    let mut map = HashMap::new();
-    let mut x = 0;
-    let mut y = 0;
-    let mut steps = 0;
-    for i in wire {
-        let mut chars = i.chars();
-        let d = chars.next().unwrap();
-        let n: isize = chars.collect::<String>().parse().unwrap();
-        let (w, h) = match d {
-            'U' => (0, 1),
-            'D' => (0, -1),
-            'R' => (1, 0),
-            'L' => (-1, 0),
-            _ => unimplemented!()
-        };
-        for _ in 0..n {
-            map.insert((x, y), (1, steps));
-            x += w;
-            y += h;
-            steps += 1;
-        };
-    }
-    map
+use aoc::output::{Format, Output};
+use aoc::parse;
+use day03::draw;
+use day03::wires;
+
+struct Options {
+    check: bool,
+    list: bool,
+    svg: Option<String>,
+    view: bool,
+    columns: usize,
+    rows: usize,
+    format: Format,
 }
 
-fn main() {
-    let stdin = io::stdin();
-    let input = stdin.lock().lines().map(|line| line.unwrap().split( ',').map(|l| l.to_owned()).collect::<Vec<String>>()).collect::<Vec<_>>();
+fn usage() -> ! {
+    eprintln!("usage: day03 [--check-input] [--crossings] [--svg FILE] [--view [--columns N] [--rows N]] [--format text|json] < input");
+    process::exit(2)
+}
 
-    let wire1 = read_wire(&input[0]);
-    let wire2 = read_wire(&input[1]);
+fn number(arg: Option<String>) -> usize {
+    arg.and_then(|n| n.parse().ok()).unwrap_or_else(|| usage())
+}
 
-    let part1 = wire2.keys().filter_map(|&(x, y)| {
-        match wire1.get(&(x, y)) {
-            Some(_) if x != 0 && y != 0 => Some(x.abs() + y.abs()),
-            _ => None
+fn parse_args() -> Options {
+    let mut options = Options { check: false, list: false, svg: None, view: false, columns: 100, rows: 40, format: Format::Text };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--check-input" => options.check = true,
+            "--crossings" => options.list = true,
+            "--svg" => options.svg = Some(args.next().unwrap_or_else(|| usage())),
+            "--view" => options.view = true,
+            "--columns" => options.columns = number(args.next()),
+            "--rows" => options.rows = number(args.next()),
+            "--format" => options.format = args.next().and_then(|f| Format::parse(&f)).unwrap_or_else(|| usage()),
+            _ => usage(),
         }
-    }).min().unwrap();
-    println!("Part 1: {}", part1);
+    }
+    options
+}
 
-    let part2 = wire2.iter().filter_map(|(&(x, y), (_, w1steps))| {
-        match wire1.get(&(x, y)) {
-            Some((_, w2steps)) if x != 0 && y != 0 => Some(w1steps + w2steps),
-            _ => None
+fn main() {
+    let options = parse_args();
+    let input = parse::read_stdin();
+    if options.check {
+        parse::check(wires::parse(&input));
+    }
+    let wires = parse::or_exit(wires::parse(&input));
+    let output = Output::new(3, options.format);
+    let crossings = output.time(|| wires::crossings(&wires));
+    if options.list {
+        for c in &crossings {
+            println!("wires {} and {} cross at {},{}: distance {}, {} steps",
+                     c.wires.0 + 1, c.wires.1 + 1, c.point.x, -c.point.y, c.distance, c.steps);
+        }
+    }
+    if let Some(file) = &options.svg {
+        if let Err(e) = fs::write(file, draw::svg(&wires, &crossings)) {
+            eprintln!("{}: {}", file, e);
+            process::exit(1);
         }
-    }).min().unwrap();
-    println!("Part 2: {}", part2);
+    }
+    if options.view {
+        print!("{}", draw::terminal(&wires, &crossings, options.columns, options.rows));
+    }
+    if crossings.is_empty() {
+        eprintln!("the wires never cross");
+        process::exit(1);
+    }
+
+    output.part(1, || crossings[0].distance);
+    output.part(2, || crossings.iter().map(|c| c.steps).min());
 }
@@ -0,0 +1,222 @@
+//! Crossed wires as lists of straight segments.
+//!
+//! Crossings are found with a sweep over x: horizontal segments enter and
+//! leave an active set ordered by y, and every vertical segment picks up the
+//! horizontals within its y range. Segments lying on the same line are
+//! checked separately for overlaps, where every shared point is a crossing.
+//!
+//! Points are in grid coordinates, with `y` growing downwards, so `U` moves
+//! to a smaller `y`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use aoc::grid::{Direction, Point};
+use aoc::parse::{self, ParseError};
+
+/// The extent of every segment on a line, keyed by whether the line is
+/// horizontal and where it lies.
+type Lines = HashMap<(bool, i64), Vec<(i64, i64, usize)>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub from: Point,
+    pub to: Point,
+    /// Steps along the wire before `from`.
+    pub steps: i64,
+}
+
+impl Segment {
+    fn is_horizontal(&self) -> bool {
+        self.from.y == self.to.y
+    }
+
+    /// Steps along the wire to reach `p`, which must be on the segment.
+    fn steps_to(&self, p: Point) -> i64 {
+        self.steps + p.manhattan(self.from)
+    }
+
+    fn xs(&self) -> (i64, i64) {
+        (self.from.x.min(self.to.x), self.from.x.max(self.to.x))
+    }
+
+    fn ys(&self) -> (i64, i64) {
+        (self.from.y.min(self.to.y), self.from.y.max(self.to.y))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wire {
+    pub segments: Vec<Segment>,
+}
+
+impl Wire {
+    /// Parses comma separated moves like `R8,U5,L5,D3` found on `line` of
+    /// the input (only used in errors).
+    pub fn parse(line: usize, source: &str) -> Result<Wire, ParseError> {
+        let mut at = Point::ORIGIN;
+        let mut steps = 0;
+        let mut segments = Vec::new();
+        for (column, text) in parse::fields(source, ',') {
+            let dir = text.chars().next()
+                .ok_or_else(|| ParseError::new(line, column, text, "a move like R8", source))?;
+            let length = &text[dir.len_utf8()..];
+            let n = length.parse::<u32>()
+                .map_err(|_| ParseError::new(line, column + 1, length, "a length", source))? as i64;
+            let to = match Direction::parse(dir).filter(|_| "UDLR".contains(dir)) {
+                Some(dir) => at.step(dir, n),
+                None => return Err(ParseError::new(line, column, &dir.to_string(), "U, D, L or R", source)),
+            };
+            segments.push(Segment { from: at, to, steps });
+            at = to;
+            steps += n;
+        }
+        Ok(Wire { segments })
+    }
+}
+
+/// One wire per line, blank lines are skipped.
+pub fn parse(input: &str) -> Result<Vec<Wire>, ParseError> {
+    parse::lines(input).map(|(line, text)| Wire::parse(line, text)).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crossing {
+    pub point: Point,
+    /// The two wires crossing, lower index first.
+    pub wires: (usize, usize),
+    /// Manhattan distance from the origin.
+    pub distance: i64,
+    /// Fewest combined steps the two wires take to reach the point.
+    pub steps: i64,
+}
+
+/// Every point where two different wires meet, apart from the origin they
+/// all start at, ordered by distance.
+pub fn crossings(wires: &[Wire]) -> Vec<Crossing> {
+    let segments = wires.iter().enumerate()
+        .flat_map(|(w, wire)| wire.segments.iter().map(move |s| (w, *s)))
+        .collect::<Vec<_>>();
+    let mut found: HashMap<((usize, usize), Point), i64> = HashMap::new();
+    let mut record = |p: Point, a: usize, b: usize| {
+        let ((wa, sa), (wb, sb)) = (segments[a], segments[b]);
+        if wa == wb || p == Point::ORIGIN {
+            return;
+        }
+        let steps = sa.steps_to(p) + sb.steps_to(p);
+        let best = found.entry(((wa.min(wb), wa.max(wb)), p)).or_insert(steps);
+        *best = (*best).min(steps);
+    };
+
+    // perpendicular crossings, horizontals are opened before and closed
+    // after the verticals at the same x
+    let mut events = Vec::new();
+    for (i, (_, s)) in segments.iter().enumerate() {
+        let (lo, hi) = s.xs();
+        if s.is_horizontal() {
+            events.push((lo, 0, i));
+            events.push((hi, 2, i));
+        } else {
+            events.push((lo, 1, i));
+        }
+    }
+    events.sort_unstable();
+    let mut active: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for (x, kind, i) in events {
+        let s = segments[i].1;
+        match kind {
+            0 => active.entry(s.from.y).or_default().push(i),
+            1 => {
+                let (lo, hi) = s.ys();
+                for (&y, horizontals) in active.range(lo..=hi) {
+                    for &h in horizontals {
+                        record(Point::new(x, y), h, i);
+                    }
+                }
+            }
+            _ => {
+                let horizontals = active.get_mut(&s.from.y).unwrap();
+                let at = horizontals.iter().position(|&h| h == i).unwrap();
+                horizontals.swap_remove(at);
+                if horizontals.is_empty() {
+                    active.remove(&s.from.y);
+                }
+            }
+        }
+    }
+
+    // collinear overlaps
+    let mut lines = Lines::new();
+    for (i, (_, s)) in segments.iter().enumerate() {
+        if s.is_horizontal() {
+            let (lo, hi) = s.xs();
+            lines.entry((true, s.from.y)).or_default().push((lo, hi, i));
+        } else {
+            let (lo, hi) = s.ys();
+            lines.entry((false, s.from.x)).or_default().push((lo, hi, i));
+        }
+    }
+    for ((horizontal, c), mut line) in lines {
+        line.sort_unstable();
+        for (k, &(_, hi, a)) in line.iter().enumerate() {
+            for &(lo2, hi2, b) in line[k + 1..].iter().take_while(|&&(lo2, _, _)| lo2 <= hi) {
+                for t in lo2..=hi.min(hi2) {
+                    record(if horizontal { Point::new(t, c) } else { Point::new(c, t) }, a, b);
+                }
+            }
+        }
+    }
+
+    let mut crossings = found.into_iter().map(|((wires, point), steps)| {
+        Crossing { point, wires, distance: point.manhattan(Point::ORIGIN), steps }
+    }).collect::<Vec<_>>();
+    crossings.sort_by_key(|c| (c.distance, c.point, c.wires));
+    crossings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wires(lines: &[&str]) -> Vec<Wire> {
+        parse(&lines.join("\n")).unwrap()
+    }
+
+    fn answers(lines: &[&str]) -> (i64, i64) {
+        let crossings = crossings(&wires(lines));
+        (crossings[0].distance, crossings.iter().map(|c| c.steps).min().unwrap())
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(answers(&["R8,U5,L5,D3", "U7,R6,D4,L4"]), (6, 30));
+        assert_eq!(answers(&["R75,D30,R83,U83,L12,D49,R71,U7,L72", "U62,R66,U55,R34,D71,R55,D58,R83"]), (159, 610));
+        assert_eq!(answers(&["R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51", "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"]), (135, 410));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let found = crossings(&wires(&["R10", "R3,U2,R4,D2,R5"]));
+        let points = found.iter().map(|c| c.point).collect::<Vec<_>>();
+        assert_eq!(points, [1, 2, 3, 7, 8, 9, 10].iter().map(|&x| Point::new(x, 0)).collect::<Vec<_>>());
+        assert_eq!(found[3].steps, 7 + 11);
+        // the second wire comes back over its own path, only its first visit counts
+        let found = crossings(&wires(&["U5", "R2,L2,U3,D3,U3"]));
+        assert_eq!(found.iter().map(|c| (c.point, c.steps)).collect::<Vec<_>>(),
+                   vec![(Point::new(0, -1), 6), (Point::new(0, -2), 8), (Point::new(0, -3), 10)]);
+    }
+
+    #[test]
+    fn test_many_wires() {
+        let found = crossings(&wires(&["R5", "U1,R2,D2", "D1,R3,U2"]));
+        assert_eq!(found.iter().map(|c| (c.wires, c.point)).collect::<Vec<_>>(),
+                   vec![((0, 1), Point::new(2, 0)), ((1, 2), Point::new(2, 1)), ((0, 2), Point::new(3, 0))]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("R1\n\nR2, X3"), Err(ParseError::new(3, 5, "X", "U, D, L or R", "R2, X3")));
+        assert_eq!(Wire::parse(1, "R2,U,L1"), Err(ParseError::new(1, 5, "", "a length", "R2,U,L1")));
+        assert_eq!(Wire::parse(1, "R2,,L1"), Err(ParseError::new(1, 4, "", "a move like R8", "R2,,L1")));
+        assert_eq!(Wire::parse(2, "R2,L-1"), Err(ParseError::new(2, 5, "-1", "a length", "R2,L-1")));
+    }
+}
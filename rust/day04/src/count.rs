@@ -0,0 +1,334 @@
+//! Counting passwords in a range with a digit dynamic program.
+//!
+//! Below the leading digits that follow the upper bound, the remaining
+//! digits are free, and how many ways they can be filled in depends only on
+//! how many there are and on the state of every rule. Those counts are
+//! memoized, so the work grows with the number of digits and the number of
+//! rule states rather than with the size of the range.
+//!
+//! The same counts rank the passwords: the password of a given rank is
+//! found digit by digit, skipping every digit whose free completions all
+//! rank lower. Listing and sampling passwords are built on that, so they
+//! never look at a number that does not pass.
+
+use std::collections::HashMap;
+
+use aoc::parse::{self, ParseError};
+
+use crate::rules::PasswordRule;
+
+/// The largest bound `range` accepts. With at most 19 digits every count
+/// and rank stays well inside a u64, and so does the number after `high`.
+pub const MAX: u64 = 9_999_999_999_999_999_999;
+
+/// Parses a puzzle input range like `138241-674034`.
+pub fn range(text: &str) -> Result<(u64, u64), ParseError> {
+    let bound = |column, field| match parse::number(1, column, field, text)? {
+        n if n > MAX => Err(ParseError::new(1, column, field, "a number of at most 19 digits", text)),
+        n => Ok(n),
+    };
+    let fields = parse::fields(text, '-').collect::<Vec<_>>();
+    match fields[..] {
+        [(a, low), (b, high)] => {
+            let (low, high) = (bound(a, low)?, bound(b, high)?);
+            if low > high {
+                return Err(ParseError::new(1, 1, text.trim(), "a range from low to high", text));
+            }
+            Ok((low, high))
+        }
+        [_] => Err(ParseError::new(1, text.trim_end().chars().count() + 1, "", "`-`", text)),
+        _ => Err(ParseError::new(1, fields[2].0 - 1, "-", "a single `-`", text)),
+    }
+}
+
+pub struct Counter<'a> {
+    rule: &'a dyn PasswordRule,
+    memo: HashMap<(usize, u64), u64>,
+}
+
+impl<'a> Counter<'a> {
+    pub fn new(rule: &'a dyn PasswordRule) -> Counter<'a> {
+        Counter { rule, memo: HashMap::new() }
+    }
+
+    /// Ways to append `left` more digits to a password in `state` so that
+    /// it passes.
+    fn free(&mut self, left: usize, state: u64) -> u64 {
+        if left == 0 {
+            return self.rule.accept(state) as u64;
+        }
+        if let Some(&n) = self.memo.get(&(left, state)) {
+            return n;
+        }
+        let mut n = 0;
+        for d in 0..10 {
+            if let Some(next) = self.rule.step(state, d) {
+                n += self.free(left - 1, next);
+            }
+        }
+        self.memo.insert((left, state), n);
+        n
+    }
+
+    /// Passing passwords in `0..=n`.
+    fn up_to(&mut self, n: u64) -> u64 {
+        let digits = n.to_string().bytes().map(|b| b - b'0').collect::<Vec<_>>();
+        let start = self.rule.start();
+        let zero = self.rule.step(start, 0).is_some_and(|s| self.rule.accept(s)) as u64;
+        // shorter passwords, without leading zeros
+        let mut total = zero;
+        for len in 1..digits.len() {
+            for d in 1..10 {
+                if let Some(state) = self.rule.step(start, d) {
+                    total += self.free(len - 1, state);
+                }
+            }
+        }
+        // as long as `n`, following its digits while every digit below them
+        // leaves the rest free
+        let mut state = start;
+        for (i, &limit) in digits.iter().enumerate() {
+            let lowest = if i == 0 { 1 } else { 0 };
+            for d in lowest..limit {
+                if let Some(next) = self.rule.step(state, d) {
+                    total += self.free(digits.len() - i - 1, next);
+                }
+            }
+            match self.rule.step(state, limit) {
+                Some(next) => state = next,
+                None => return total,
+            }
+        }
+        if n > 0 && self.rule.accept(state) {
+            total += 1;
+        }
+        total
+    }
+
+    /// Passing passwords in `low..=high`, which must not go past `MAX`.
+    pub fn count(&mut self, low: u64, high: u64) -> u64 {
+        if low > high {
+            return 0;
+        }
+        self.up_to(high) - self.below(low)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 { 0 } else { self.up_to(n - 1) }
+    }
+
+    /// The digit to follow `state` with `left` digits after it, taking
+    /// `rank` past the passwords under every smaller digit.
+    fn pick(&mut self, state: u64, left: usize, lowest: u8, rank: &mut u64) -> Option<(u8, u64)> {
+        for d in lowest..10 {
+            if let Some(next) = self.rule.step(state, d) {
+                let n = self.free(left, next);
+                if *rank < n {
+                    return Some((d, next));
+                }
+                *rank -= n;
+            }
+        }
+        None
+    }
+
+    /// The smallest digit from `lowest` up that can follow `state` with
+    /// `left` digits after it and still pass.
+    fn smallest(&mut self, state: u64, left: usize, lowest: u8) -> Option<(u8, u64)> {
+        for d in lowest..10 {
+            if let Some(next) = self.rule.step(state, d) {
+                if self.free(left, next) > 0 {
+                    return Some((d, next));
+                }
+            }
+        }
+        None
+    }
+
+    /// The passing password with `rank` passing passwords below it.
+    pub fn select(&mut self, mut rank: u64) -> Option<u64> {
+        let start = self.rule.start();
+        if self.rule.step(start, 0).is_some_and(|s| self.rule.accept(s)) {
+            if rank == 0 {
+                return Some(0);
+            }
+            rank -= 1;
+        }
+        for len in 1..=20 {
+            let (first, mut state) = match self.pick(start, len - 1, 1, &mut rank) {
+                Some(found) => found,
+                None => continue,
+            };
+            let mut n = first as u64;
+            for left in (0..len - 1).rev() {
+                let (d, next) = self.pick(state, left, 0, &mut rank)?;
+                n = n.checked_mul(10)?.checked_add(d as u64)?;
+                state = next;
+            }
+            return Some(n);
+        }
+        None
+    }
+
+    /// The `k`th passing password in `low..=high`, counting from 0.
+    pub fn nth(&mut self, low: u64, high: u64, k: u64) -> Option<u64> {
+        if k >= self.count(low, high) {
+            return None;
+        }
+        let rank = self.below(low) + k;
+        self.select(rank)
+    }
+
+    /// The passing passwords in `low..=high` in increasing order.
+    pub fn passwords(mut self, low: u64, high: u64) -> Passwords<'a> {
+        let left = self.count(low, high);
+        let first = if left > 0 { self.nth(low, high, 0) } else { None };
+        let digits = first.map_or(Vec::new(), |n| n.to_string().bytes().map(|b| b - b'0').collect());
+        let mut states = vec![self.rule.start()];
+        for &d in &digits {
+            let next = self.rule.step(*states.last().unwrap(), d).unwrap();
+            states.push(next);
+        }
+        Passwords { counter: self, digits, states, left, started: false }
+    }
+}
+
+/// Walks from one passing password to the next without ranking each one:
+/// the last digit that can still grow is bumped to the next digit with
+/// passing completions, and the digits after it are filled in with the
+/// smallest ones that still have some.
+pub struct Passwords<'a> {
+    counter: Counter<'a>,
+    digits: Vec<u8>,
+    /// `states[i]` is the state before `digits[i]`.
+    states: Vec<u64>,
+    left: u64,
+    started: bool,
+}
+
+impl<'a> Passwords<'a> {
+    /// Fills in `digits[from..len]` with the smallest digits that can pass.
+    fn fill(&mut self, from: usize, len: usize) -> bool {
+        for i in from..len {
+            let lowest = if i == 0 && len > 1 { 1 } else { 0 };
+            match self.counter.smallest(self.states[i], len - i - 1, lowest) {
+                Some((d, next)) => {
+                    self.digits.push(d);
+                    self.states.push(next);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn advance(&mut self) {
+        let len = self.digits.len();
+        for i in (0..len).rev() {
+            if let Some((d, next)) = self.counter.smallest(self.states[i], len - i - 1, self.digits[i] + 1) {
+                self.digits.truncate(i);
+                self.states.truncate(i + 1);
+                self.digits.push(d);
+                self.states.push(next);
+                self.fill(i + 1, len);
+                return;
+            }
+        }
+        for len in len + 1..=20 {
+            self.digits.clear();
+            self.states.truncate(1);
+            if self.fill(0, len) {
+                return;
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Passwords<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.left == 0 {
+            return None;
+        }
+        if self.started {
+            self.advance();
+        }
+        self.started = true;
+        self.left -= 1;
+        Some(self.digits.iter().fold(0, |n, &d| n * 10 + d as u64))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left as usize, Some(self.left as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rules;
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range("138241-674034"), Ok((138_241, 674_034)));
+        assert_eq!(range("5 - 7"), Ok((5, 7)));
+        assert_eq!(range("12-x4"), Err(ParseError::new(1, 4, "x4", "a number", "12-x4")));
+        assert_eq!(range("12"), Err(ParseError::new(1, 3, "", "`-`", "12")));
+        assert_eq!(range("1-2-3"), Err(ParseError::new(1, 4, "-", "a single `-`", "1-2-3")));
+        assert_eq!(range("1-9999999999999999999"), Ok((1, MAX)));
+        assert_eq!(range("1-18446744073709551615"),
+                   Err(ParseError::new(1, 3, "18446744073709551615", "a number of at most 19 digits", "1-18446744073709551615")));
+        assert_eq!(range("9-3"), Err(ParseError::new(1, 1, "9-3", "a range from low to high", "9-3")));
+    }
+
+    fn brute(low: u64, high: u64, rule: &dyn PasswordRule) -> u64 {
+        (low..=high).filter(|n| {
+            let digits = n.to_string().bytes().map(|b| b - b'0').collect::<Vec<_>>();
+            rule.check(&digits)
+        }).count() as u64
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let sets = ["len>=0", "pair", "run==2", "pair & nondecreasing", "run==2 & nondecreasing",
+                    "!has:0 | run>=3", "range:150..1100 & nonincreasing"];
+        for text in &sets {
+            let rule = rules::parse(text).unwrap();
+            let mut counter = Counter::new(&*rule);
+            for &(low, high) in &[(0, 0), (0, 9), (5, 1234), (99, 100), (11111, 13000), (138_241, 140_000), (7, 3)] {
+                assert_eq!(counter.count(low, high), brute(low, high, &*rule), "{} in {}..={}", text, low, high);
+            }
+        }
+    }
+
+    #[test]
+    fn test_enumerate() {
+        for text in &["pair & nondecreasing", "run==2 & !has:5", "nonincreasing | len<=2"] {
+            let rule = rules::parse(text).unwrap();
+            let expected = (90..=12_345).filter(|n| {
+                rule.check(&n.to_string().bytes().map(|b| b - b'0').collect::<Vec<_>>())
+            }).collect::<Vec<_>>();
+            assert_eq!(Counter::new(&*rule).passwords(90, 12_345).collect::<Vec<_>>(), expected, "{}", text);
+            let mut counter = Counter::new(&*rule);
+            assert_eq!(counter.nth(90, 12_345, 10), Some(expected[10]));
+            assert_eq!(counter.nth(90, 12_345, expected.len() as u64), None);
+        }
+        let rule = rules::parse("nondecreasing & run==2").unwrap();
+        let mut counter = Counter::new(&*rule);
+        assert_eq!(counter.nth(100_000_000_000_000_000, 999_999_999_999_999_999, 0), Some(111_111_111_111_111_122));
+        assert_eq!(counter.select(0), Some(11));
+        assert_eq!(Counter::new(&*rule).passwords(5, 1).next(), None);
+    }
+
+    #[test]
+    fn test_long_passwords() {
+        // non-decreasing 18 digit numbers have no zeros: 18 digits out of
+        // 1 to 9 with repetition, C(26, 8)
+        let rule = rules::parse("nondecreasing").unwrap();
+        assert_eq!(Counter::new(&*rule).count(100_000_000_000_000_000, 999_999_999_999_999_999), 1_562_275);
+        let rule = rules::parse("len>=0").unwrap();
+        assert_eq!(Counter::new(&*rule).count(0, 9_999_999_999_999_999_999), 10_000_000_000_000_000_000);
+    }
+}
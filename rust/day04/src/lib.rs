@@ -0,0 +1,4 @@
+//! Secure Container of day 4.
+
+pub mod count;
+pub mod rules;
@@ -1,27 +1,131 @@
-fn no_large_group(pw: &[u8]) -> bool {
-    let mut last = pw[0];
-    let mut last_count = 1;
-    for &p in &pw[1..] {
-        if p == last {
-            last_count += 1;
-        } else {
-            if last_count == 2 {
-                return true;
+use std::env;
+use std::io;
+use std::io::prelude::*;
+use std::process;
+
+use aoc::output::{Format, Output};
+use aoc::parse;
+use aoc::rng::XorShift;
+use day04::count::{self, Counter};
+use day04::rules::{self, PasswordRule, PART1, PART2};
+
+enum Listing {
+    All,
+    First(u64),
+    Nth(u64),
+    Sample(u64),
+}
+
+struct Options {
+    check: bool,
+    range: (u64, u64),
+    rule: Option<String>,
+    listing: Option<Listing>,
+    seed: u64,
+    format: Format,
+}
+
+fn usage() -> ! {
+    eprintln!("usage: day04 [LOW-HIGH] [--check-input] [--rules RULE] [--list | --first N | --nth K | --sample N [--seed S]] [--format text|json]");
+    eprintln!("for example: day04 100000-999999 --rules 'nondecreasing & run==2 & !has:0'");
+    eprintln!("the listings use the rules of part 2 unless --rules is given, and --nth counts from 1");
+    process::exit(2)
+}
+
+fn number(arg: Option<String>) -> u64 {
+    arg.and_then(|n| n.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn parse_args() -> Options {
+    let mut options = Options { check: false, range: (138_241, 674_034), rule: None, listing: None, seed: 1, format: Format::Text };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--check-input" => options.check = true,
+            "--rules" => options.rule = Some(args.next().unwrap_or_else(|| usage())),
+            "--list" => options.listing = Some(Listing::All),
+            "--first" => options.listing = Some(Listing::First(number(args.next()))),
+            "--nth" => options.listing = Some(Listing::Nth(Some(number(args.next())).filter(|&k| k > 0).unwrap_or_else(|| usage()))),
+            "--sample" => options.listing = Some(Listing::Sample(number(args.next()))),
+            "--seed" => options.seed = number(args.next()),
+            "--format" => options.format = args.next().and_then(|f| Format::parse(&f)).unwrap_or_else(|| usage()),
+            _ if arg.starts_with("--") => usage(),
+            _ => options.range = count::range(&arg).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(2)
+            }),
+        }
+    }
+    options
+}
+
+fn parse_rule(rule: &str) -> Box<dyn PasswordRule> {
+    rules::parse(rule).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2)
+    })
+}
+
+/// Prints the passwords a listing asks for, one per line.
+fn list(rule: &dyn PasswordRule, (low, high): (u64, u64), listing: &Listing, seed: u64) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut counter = Counter::new(rule);
+    match *listing {
+        Listing::All => {
+            for password in counter.passwords(low, high) {
+                writeln!(out, "{}", password)?;
+            }
+        }
+        Listing::First(n) => {
+            for password in counter.passwords(low, high).take(n as usize) {
+                writeln!(out, "{}", password)?;
+            }
+        }
+        // the first password is the 1st, and `--nth 0` is turned away
+        Listing::Nth(k) => match counter.nth(low, high, k - 1) {
+            Some(password) => writeln!(out, "{}", password)?,
+            None => {
+                eprintln!("there are only {} passwords", counter.count(low, high));
+                process::exit(1)
+            }
+        },
+        Listing::Sample(n) => {
+            let total = counter.count(low, high);
+            if total == 0 && n > 0 {
+                eprintln!("there are no passwords to sample");
+                process::exit(1)
+            }
+            let mut rng = XorShift::new(seed);
+            for _ in 0..n {
+                let k = rng.below(total);
+                writeln!(out, "{}", counter.nth(low, high, k).unwrap())?;
             }
-            last = p;
-            last_count = 1;
         }
     }
-    last_count == 2
+    out.flush()
 }
 
 fn main() {
-    let two_same = |pw: &[u8]| pw.windows(2).any(|w| w[0] == w[1]);
-    let never_dec = |pw: &[u8]| pw.windows(2).all(|w| w[0] <= w[1]);
-
-    let part1 = (138241..=674034).map(|n| n.to_string()).filter(|pw| two_same(pw.as_bytes()) && never_dec(pw.as_bytes())).count();
-    let part2 = (138241..=674034).map(|n| n.to_string()).filter(|pw| two_same(pw.as_bytes()) && never_dec(pw.as_bytes()) && no_large_group(pw.as_bytes())).count();
+    let options = parse_args();
+    let (low, high) = options.range;
+    if options.check {
+        parse::check(rules::parse(options.rule.as_deref().unwrap_or(PART2)));
+    }
 
-    println!("Part 2: {}", part1);
-    println!("Part 2: {}", part2);
+    if let Some(listing) = &options.listing {
+        let rule = parse_rule(options.rule.as_deref().unwrap_or(PART2));
+        if let Err(e) = list(&*rule, options.range, listing, options.seed) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+    let output = Output::new(4, options.format);
+    if let Some(rule) = &options.rule {
+        output.other(|| Counter::new(&*parse_rule(rule)).count(low, high));
+        return;
+    }
+    output.part(1, || Counter::new(&*parse_rule(PART1)).count(low, high));
+    output.part(2, || Counter::new(&*parse_rule(PART2)).count(low, high));
 }
@@ -0,0 +1,540 @@
+//! Password rules, as automata reading the digits of a password from the
+//! most significant one.
+//!
+//! Every rule numbers its states from zero up to `states()`, which is what
+//! lets rules be combined and lets the counter share work between passwords
+//! that reach the same state. Rules are written in a small language:
+//!
+//! ```text
+//! pair                 two adjacent digits are the same
+//! run==N, run>=N       a run of the same digit exactly / at least N long
+//! nondecreasing        digits never decrease left to right
+//! nonincreasing        digits never increase left to right
+//! digits:SET           every digit is one of SET, e.g. digits:13579
+//! has:SET              some digit is one of SET
+//! len==N               the password is N digits long, also <=, >=, < and >
+//! range:LOW..HIGH      the password is between LOW and HIGH inclusive
+//! A & B, A | B, !A, (A)
+//! ```
+
+use std::fmt;
+
+/// The rules of the two parts of the puzzle.
+pub const PART1: &str = "pair & nondecreasing";
+pub const PART2: &str = "run==2 & nondecreasing";
+
+/// The last digit before any digit is read.
+const NONE: u64 = 10;
+
+pub trait PasswordRule: fmt::Debug {
+    /// How many states the rule has, states are numbered from 0.
+    fn states(&self) -> u64;
+
+    fn start(&self) -> u64;
+
+    /// The state after reading `digit`, `None` if no password continuing
+    /// this way can pass.
+    fn step(&self, state: u64, digit: u8) -> Option<u64>;
+
+    fn accept(&self, state: u64) -> bool;
+
+    /// Runs the rule over a whole password.
+    fn check(&self, password: &[u8]) -> bool {
+        password.iter().try_fold(self.start(), |state, &d| self.step(state, d)).is_some_and(|s| self.accept(s))
+    }
+}
+
+/// A run of the same digit exactly, or at least, `len` long.
+#[derive(Debug)]
+pub struct Run {
+    pub len: u64,
+    pub exact: bool,
+}
+
+impl PasswordRule for Run {
+    // last digit + 11 * (length of the current run + (len + 2) * seen)
+    fn states(&self) -> u64 {
+        11 * (self.len + 2) * 2
+    }
+
+    fn start(&self) -> u64 {
+        NONE
+    }
+
+    fn step(&self, state: u64, digit: u8) -> Option<u64> {
+        let (last, run, mut seen) = (state % 11, state / 11 % (self.len + 2), state / 11 / (self.len + 2));
+        let run = if last == digit as u64 {
+            (run + 1).min(self.len + 1)
+        } else {
+            seen |= (self.exact && run == self.len) as u64;
+            1
+        };
+        seen |= (!self.exact && run >= self.len) as u64;
+        Some(digit as u64 + 11 * (run + (self.len + 2) * seen))
+    }
+
+    fn accept(&self, state: u64) -> bool {
+        let (run, seen) = (state / 11 % (self.len + 2), state / 11 / (self.len + 2));
+        seen == 1 || run == self.len
+    }
+}
+
+#[derive(Debug)]
+pub enum Order {
+    NonDecreasing,
+    NonIncreasing,
+}
+
+impl PasswordRule for Order {
+    // the last digit
+    fn states(&self) -> u64 {
+        11
+    }
+
+    fn start(&self) -> u64 {
+        NONE
+    }
+
+    fn step(&self, last: u64, digit: u8) -> Option<u64> {
+        let digit = digit as u64;
+        let ok = last == NONE || match self {
+            Order::NonDecreasing => digit >= last,
+            Order::NonIncreasing => digit <= last,
+        };
+        if ok { Some(digit) } else { None }
+    }
+
+    fn accept(&self, _: u64) -> bool {
+        true
+    }
+}
+
+/// Every digit (`every`), or some digit, is in `set`.
+#[derive(Debug)]
+pub struct Digits {
+    pub set: [bool; 10],
+    pub every: bool,
+}
+
+impl PasswordRule for Digits {
+    // whether a digit in the set was seen
+    fn states(&self) -> u64 {
+        2
+    }
+
+    fn start(&self) -> u64 {
+        0
+    }
+
+    fn step(&self, seen: u64, digit: u8) -> Option<u64> {
+        match (self.set[digit as usize], self.every) {
+            (false, true) => None,
+            (inside, _) => Some(seen | inside as u64),
+        }
+    }
+
+    fn accept(&self, seen: u64) -> bool {
+        self.every || seen == 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Cmp {
+    fn holds(self, a: u64, b: u64) -> bool {
+        match self {
+            Cmp::Lt => a < b,
+            Cmp::Le => a <= b,
+            Cmp::Eq => a == b,
+            Cmp::Ge => a >= b,
+            Cmp::Gt => a > b,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Length {
+    pub cmp: Cmp,
+    pub len: u64,
+}
+
+impl PasswordRule for Length {
+    // digits read, counting no further than one past `len`
+    fn states(&self) -> u64 {
+        self.len + 2
+    }
+
+    fn start(&self) -> u64 {
+        0
+    }
+
+    fn step(&self, read: u64, _: u8) -> Option<u64> {
+        Some((read + 1).min(self.len + 1))
+    }
+
+    fn accept(&self, read: u64) -> bool {
+        self.cmp.holds(read, self.len)
+    }
+}
+
+/// Compares a password being read against a fixed number: the state is the
+/// digits read (no further than one past the number's length) and how those
+/// digits compare with the same number of leading digits of the number.
+#[derive(Debug)]
+struct Bound {
+    digits: Vec<u8>,
+}
+
+impl Bound {
+    fn new(n: u64) -> Bound {
+        Bound { digits: n.to_string().bytes().map(|b| b - b'0').collect() }
+    }
+
+    fn states(&self) -> u64 {
+        (self.digits.len() as u64 + 2) * 3
+    }
+
+    // 0 less, 1 equal, 2 greater
+    fn step(&self, state: u64, digit: u8) -> u64 {
+        let (read, order) = (state / 3, state % 3);
+        let order = match self.digits.get(read as usize) {
+            Some(&d) if order == 1 => 1 + (digit > d) as u64 - (digit < d) as u64,
+            _ => order,
+        };
+        (read + 1).min(self.digits.len() as u64 + 1) * 3 + order
+    }
+
+    /// How the whole password compares with the number.
+    fn order(&self, state: u64) -> std::cmp::Ordering {
+        let (read, order) = (state / 3, state % 3);
+        (read as usize).cmp(&self.digits.len()).then(order.cmp(&1))
+    }
+}
+
+#[derive(Debug)]
+pub struct Range {
+    low: Bound,
+    high: Bound,
+}
+
+impl Range {
+    pub fn new(low: u64, high: u64) -> Range {
+        Range { low: Bound::new(low), high: Bound::new(high) }
+    }
+}
+
+impl PasswordRule for Range {
+    fn states(&self) -> u64 {
+        self.low.states() * self.high.states()
+    }
+
+    fn start(&self) -> u64 {
+        // nothing read, equal so far
+        1 + self.low.states()
+    }
+
+    fn step(&self, state: u64, digit: u8) -> Option<u64> {
+        let n = self.low.states();
+        Some(self.low.step(state % n, digit) + n * self.high.step(state / n, digit))
+    }
+
+    fn accept(&self, state: u64) -> bool {
+        let n = self.low.states();
+        self.low.order(state % n).is_ge() && self.high.order(state / n).is_le()
+    }
+}
+
+#[derive(Debug)]
+pub struct And(pub Box<dyn PasswordRule>, pub Box<dyn PasswordRule>);
+
+impl PasswordRule for And {
+    fn states(&self) -> u64 {
+        self.0.states() * self.1.states()
+    }
+
+    fn start(&self) -> u64 {
+        self.0.start() + self.0.states() * self.1.start()
+    }
+
+    fn step(&self, state: u64, digit: u8) -> Option<u64> {
+        let n = self.0.states();
+        Some(self.0.step(state % n, digit)? + n * self.1.step(state / n, digit)?)
+    }
+
+    fn accept(&self, state: u64) -> bool {
+        let n = self.0.states();
+        self.0.accept(state % n) && self.1.accept(state / n)
+    }
+}
+
+/// Either rule passes. A side that can no longer pass is kept in an extra
+/// dead state, numbered after its own states.
+#[derive(Debug)]
+pub struct Or(pub Box<dyn PasswordRule>, pub Box<dyn PasswordRule>);
+
+impl PasswordRule for Or {
+    fn states(&self) -> u64 {
+        (self.0.states() + 1) * (self.1.states() + 1)
+    }
+
+    fn start(&self) -> u64 {
+        self.0.start() + (self.0.states() + 1) * self.1.start()
+    }
+
+    fn step(&self, state: u64, digit: u8) -> Option<u64> {
+        let (n, m) = (self.0.states(), self.1.states());
+        let a = if state % (n + 1) == n { n } else { self.0.step(state % (n + 1), digit).unwrap_or(n) };
+        let b = if state / (n + 1) == m { m } else { self.1.step(state / (n + 1), digit).unwrap_or(m) };
+        if a == n && b == m { None } else { Some(a + (n + 1) * b) }
+    }
+
+    fn accept(&self, state: u64) -> bool {
+        let (n, m) = (self.0.states(), self.1.states());
+        let (a, b) = (state % (n + 1), state / (n + 1));
+        a != n && self.0.accept(a) || b != m && self.1.accept(b)
+    }
+}
+
+/// The rule fails. Once the rule can no longer pass, this stays in an extra
+/// state that always passes.
+#[derive(Debug)]
+pub struct Not(pub Box<dyn PasswordRule>);
+
+impl PasswordRule for Not {
+    fn states(&self) -> u64 {
+        self.0.states() + 1
+    }
+
+    fn start(&self) -> u64 {
+        self.0.start()
+    }
+
+    fn step(&self, state: u64, digit: u8) -> Option<u64> {
+        let dead = self.0.states();
+        if state == dead { Some(dead) } else { Some(self.0.step(state, digit).unwrap_or(dead)) }
+    }
+
+    fn accept(&self, state: u64) -> bool {
+        state == self.0.states() || !self.0.accept(state)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RuleError {
+    UnknownRule(String),
+    /// Expected `what` at token `position`, found something else.
+    Expected { what: &'static str, position: usize },
+    /// The combined rule has more states than fit in 64 bits.
+    TooComplex,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::UnknownRule(text) => write!(f, "unknown rule {:?}", text),
+            RuleError::Expected { what, position } => write!(f, "expected {} at token {}", what, position + 1),
+            RuleError::TooComplex => write!(f, "the rule is too complex to evaluate"),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || "&|!()".contains(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    at: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.tokens.get(self.at).map(String::as_str) == Some(token);
+        self.at += found as usize;
+        found
+    }
+
+    fn checked(rule: Box<dyn PasswordRule>, left: u64, right: u64) -> Result<Box<dyn PasswordRule>, RuleError> {
+        (left + 1).checked_mul(right + 1).ok_or(RuleError::TooComplex)?;
+        Ok(rule)
+    }
+
+    // expr := term ('|' term)*
+    fn expr(&mut self) -> Result<Box<dyn PasswordRule>, RuleError> {
+        let mut rule = self.term()?;
+        while self.eat("|") {
+            let right = self.term()?;
+            let (a, b) = (rule.states(), right.states());
+            rule = Parser::checked(Box::new(Or(rule, right)), a, b)?;
+        }
+        Ok(rule)
+    }
+
+    // term := factor ('&' factor)*
+    fn term(&mut self) -> Result<Box<dyn PasswordRule>, RuleError> {
+        let mut rule = self.factor()?;
+        while self.eat("&") {
+            let right = self.factor()?;
+            let (a, b) = (rule.states(), right.states());
+            rule = Parser::checked(Box::new(And(rule, right)), a, b)?;
+        }
+        Ok(rule)
+    }
+
+    // factor := '!' factor | '(' expr ')' | rule
+    fn factor(&mut self) -> Result<Box<dyn PasswordRule>, RuleError> {
+        if self.eat("!") {
+            let rule = self.factor()?;
+            let n = rule.states();
+            return Parser::checked(Box::new(Not(rule)), n, 0);
+        }
+        if self.eat("(") {
+            let rule = self.expr()?;
+            if !self.eat(")") {
+                return Err(RuleError::Expected { what: "`)`", position: self.at });
+            }
+            return Ok(rule);
+        }
+        match self.tokens.get(self.at) {
+            Some(word) if !"&|)".contains(word.as_str()) => {
+                self.at += 1;
+                rule(word)
+            }
+            _ => Err(RuleError::Expected { what: "a rule", position: self.at }),
+        }
+    }
+}
+
+/// Splits a comparison like `==6` into the operator and the rest.
+fn cmp(text: &str) -> Option<(Cmp, &str)> {
+    [("==", Cmp::Eq), ("<=", Cmp::Le), (">=", Cmp::Ge), ("<", Cmp::Lt), (">", Cmp::Gt)].iter()
+        .find(|(op, _)| text.starts_with(op))
+        .map(|&(op, cmp)| (cmp, &text[op.len()..]))
+}
+
+fn rule(word: &str) -> Result<Box<dyn PasswordRule>, RuleError> {
+    let unknown = || RuleError::UnknownRule(word.to_string());
+    let number = |text: &str| text.parse::<u64>().map_err(|_| unknown());
+    let set = |text: &str| {
+        let mut set = [false; 10];
+        for c in text.chars() {
+            set[c.to_digit(10).ok_or_else(unknown)? as usize] = true;
+        }
+        Ok(set)
+    };
+    let rule: Box<dyn PasswordRule> = match word {
+        "pair" => Box::new(Run { len: 2, exact: false }),
+        "nondecreasing" => Box::new(Order::NonDecreasing),
+        "nonincreasing" => Box::new(Order::NonIncreasing),
+        _ if word.starts_with("digits:") => Box::new(Digits { set: set(&word[7..])?, every: true }),
+        _ if word.starts_with("has:") => Box::new(Digits { set: set(&word[4..])?, every: false }),
+        // a run has at least one digit, and none is longer than the 20
+        // digits of a u64 either
+        _ if word.starts_with("run") => match cmp(&word[3..]) {
+            Some((cmp @ (Cmp::Eq | Cmp::Ge), n)) => match number(n)? {
+                0 => return Err(unknown()),
+                len => Box::new(Run { len: len.min(21), exact: matches!(cmp, Cmp::Eq) }),
+            },
+            _ => return Err(unknown()),
+        },
+        _ if word.starts_with("len") => match cmp(&word[3..]) {
+            // no u64 has more than 20 digits, so longer lengths all behave alike
+            Some((cmp, n)) => Box::new(Length { cmp, len: number(n)?.min(21) }),
+            _ => return Err(unknown()),
+        },
+        _ if word.starts_with("range:") => match word[6..].split_once("..") {
+            Some((low, high)) => Box::new(Range::new(number(low)?, number(high)?)),
+            None => return Err(unknown()),
+        },
+        _ => return Err(unknown()),
+    };
+    Ok(rule)
+}
+
+/// Parses a rule written in the language above.
+pub fn parse(text: &str) -> Result<Box<dyn PasswordRule>, RuleError> {
+    let mut parser = Parser { tokens: tokenize(text), at: 0 };
+    let rule = parser.expr()?;
+    if parser.at < parser.tokens.len() {
+        return Err(RuleError::Expected { what: "`&` or `|`", position: parser.at });
+    }
+    Ok(rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &str, password: u64) -> bool {
+        let digits = password.to_string().bytes().map(|b| b - b'0').collect::<Vec<_>>();
+        parse(rule).unwrap().check(&digits)
+    }
+
+    #[test]
+    fn test_rules() {
+        assert!(check("run==2", 112233) && check("run==2", 111122));
+        assert!(!check("run==2", 123444) && check("pair", 123444) && check("run>=3", 123444));
+        assert!(check("nondecreasing", 111123) && !check("nondecreasing", 223450));
+        assert!(check("nonincreasing", 988710) && !check("nonincreasing", 988719));
+        assert!(check("digits:13579", 1379) && !check("digits:13579", 1389));
+        assert!(check("has:0", 1000) && !check("has:0", 1234));
+        assert!(check("len==6", 123456) && check("len<6", 12345) && !check("len>6", 123456));
+        assert!(!check("run==18446744073709551615", 111111) && !check("run>=18446744073709551615", 111111));
+        assert!(check("run>=20", 11_111_111_111_111_111_111));
+        for n in 0..1200 {
+            assert_eq!(check("range:7..1050", n), (7..=1050).contains(&n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_combinators() {
+        assert!(check("nondecreasing & run==2", 112233));
+        assert!(!check("nondecreasing & run==2", 123444));
+        assert!(check("nondecreasing | nonincreasing", 4321));
+        assert!(!check("nondecreasing | nonincreasing", 4231));
+        // the negated rule passes once the inner one is dead
+        assert!(check("!nondecreasing", 4231) && !check("!nondecreasing", 1234));
+        assert!(check("!(pair | has:0) & len==3", 123));
+        assert!(!check("!(pair | has:0) & len==3", 103));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("pair &").unwrap_err(), RuleError::Expected { what: "a rule", position: 2 });
+        assert_eq!(parse("(pair").unwrap_err(), RuleError::Expected { what: "`)`", position: 2 });
+        assert_eq!(parse("pair pair").unwrap_err(), RuleError::Expected { what: "`&` or `|`", position: 1 });
+        assert_eq!(parse("run<2").unwrap_err(), RuleError::UnknownRule("run<2".to_string()));
+        assert_eq!(parse("run==0").unwrap_err(), RuleError::UnknownRule("run==0".to_string()));
+        assert_eq!(parse("run>=0").unwrap_err(), RuleError::UnknownRule("run>=0".to_string()));
+        assert!(parse("run==18446744073709551615").is_ok());
+        assert_eq!(parse("digits:1a").unwrap_err(), RuleError::UnknownRule("digits:1a".to_string()));
+        let huge = ["range:1..99999999999999"; 12].join(" & ");
+        assert_eq!(parse(&huge).unwrap_err(), RuleError::TooComplex);
+    }
+}
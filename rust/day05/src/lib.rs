@@ -0,0 +1,3 @@
+//! Sunny with a Chance of Asteroids, day 5.
+
+pub mod diagnostic;
@@ -0,0 +1,96 @@
+//! Drawing the orbit map: Graphviz DOT for the whole picture and an indented
+//! tree for the terminal. Satellites are sorted by name so the output is
+//! stable.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::orbits::{BodyId, OrbitMap};
+
+fn sorted_children(map: &OrbitMap, body: BodyId) -> Vec<BodyId> {
+    let mut children = map.children(body).to_vec();
+    children.sort_by_key(|&c| map.name(c));
+    children
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A DOT digraph with an edge from every center to its satellites. The
+/// bodies and orbits along `path` are drawn in red.
+pub fn dot(map: &OrbitMap, path: &[BodyId]) -> String {
+    let on_path = path.iter().copied().collect::<HashSet<_>>();
+    let path_edges = path.windows(2).map(|w| (w[0].min(w[1]), w[0].max(w[1]))).collect::<HashSet<_>>();
+    let mut out = String::from("digraph orbits {\n    rankdir=LR;\n    node [shape=circle, fontsize=10];\n");
+    let mut stack = vec![map.root()];
+    while let Some(body) = stack.pop() {
+        let name = quote(map.name(body));
+        if on_path.contains(&body) {
+            writeln!(out, "    {} [color=red, fontcolor=red];", name).unwrap();
+        }
+        for c in sorted_children(map, body).into_iter().rev() {
+            let style = if path_edges.contains(&(body.min(c), body.max(c))) { " [color=red, penwidth=2]" } else { "" };
+            writeln!(out, "    {} -> {}{};", name, quote(map.name(c)), style).unwrap();
+            stack.push(c);
+        }
+    }
+    out + "}\n"
+}
+
+/// The map as an indented tree. Below `max_depth` subtrees are collapsed
+/// into a count of the bodies they hold.
+pub fn tree(map: &OrbitMap, max_depth: Option<usize>) -> String {
+    let mut out = String::new();
+    // (body, indentation before it, is it the last satellite of its center)
+    let mut stack = vec![(map.root(), String::new(), true)];
+    while let Some((body, prefix, last)) = stack.pop() {
+        let depth = map.depth(body);
+        let branch = match depth {
+            0 => "",
+            _ if last => "└── ",
+            _ => "├── ",
+        };
+        let collapsed = max_depth == Some(depth) && !map.children(body).is_empty();
+        if collapsed {
+            writeln!(out, "{}{}{} (+{})", prefix, branch, map.name(body), map.subtree_size(body) - 1).unwrap();
+            continue;
+        }
+        writeln!(out, "{}{}{}", prefix, branch, map.name(body)).unwrap();
+        let inner = match depth {
+            0 => String::new(),
+            _ if last => prefix + "    ",
+            _ => prefix + "│   ",
+        };
+        for (i, c) in sorted_children(map, body).into_iter().enumerate().rev() {
+            stack.push((c, inner.clone(), i + 1 == map.children(body).len()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN\n";
+
+    #[test]
+    fn test_tree() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        assert_eq!(tree(&map, Some(3)), "COM\n└── B\n    ├── C\n    │   └── D (+8)\n    └── G\n        └── H\n");
+        assert_eq!(tree(&map, None).lines().count(), map.len());
+        assert!(tree(&map, None).contains("    │       └── I\n    │           └── SAN\n"));
+    }
+
+    #[test]
+    fn test_dot() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        let path = map.path(map.id("YOU").unwrap(), map.id("SAN").unwrap());
+        let dot = dot(&map, &path);
+        assert_eq!(dot.matches(" -> ").count(), map.len() - 1);
+        assert_eq!(dot.matches("penwidth").count(), 6);
+        assert!(dot.contains("    \"D\" -> \"I\" [color=red, penwidth=2];\n"));
+        assert!(dot.contains("    \"B\" -> \"G\";\n"));
+    }
+}
@@ -0,0 +1,4 @@
+//! Universal Orbit Map of day 6.
+
+pub mod draw;
+pub mod orbits;
@@ -1,42 +1,128 @@
+use std::env;
+use std::fs;
 use std::io;
 use std::io::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::process;
+
+use aoc::output::{Format, Output};
+use aoc::parse;
+use day06::draw;
+use day06::orbits::{self, Ancestors, OrbitMap};
+
+struct Options {
+    check: bool,
+    dot: Option<String>,
+    tree: bool,
+    depth: Option<usize>,
+    queries: Vec<(String, String)>,
+    format: Format,
+}
+
+fn usage() -> ! {
+    eprintln!("usage: day06 [--check-input] [--dot FILE] [--tree [--depth N]] [--format text|json] < input");
+    eprintln!("       day06 [--query A B]... [--queries FILE]... < input");
+    process::exit(2)
+}
+
+/// The pairs of body names in `file`, exiting if it can not be read or
+/// parsed.
+fn read_queries(file: &str) -> Vec<(String, String)> {
+    let text = fs::read_to_string(file).unwrap_or_else(|e| {
+        eprintln!("{}: {}", file, e);
+        process::exit(1)
+    });
+    orbits::queries(&text).unwrap_or_else(|e| {
+        eprintln!("{}: {}", file, e);
+        process::exit(1)
+    })
+}
+
+fn parse_args() -> Options {
+    let mut options = Options { check: false, dot: None, tree: false, depth: None, queries: Vec::new(), format: Format::Text };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--check-input" => options.check = true,
+            "--tree" => options.tree = true,
+            "--dot" => options.dot = Some(args.next().unwrap_or_else(|| usage())),
+            "--depth" => options.depth = Some(args.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| usage())),
+            "--query" => match (args.next(), args.next()) {
+                (Some(a), Some(b)) => options.queries.push((a, b)),
+                _ => usage(),
+            },
+            "--format" => options.format = args.next().and_then(|f| Format::parse(&f)).unwrap_or_else(|| usage()),
+            "--queries" => options.queries.extend(read_queries(&args.next().unwrap_or_else(|| usage()))),
+            _ => usage(),
+        }
+    }
+    options
+}
+
+/// Answers every query with the transfers between what the two bodies orbit
+/// and the bodies passed on the way, and tells whether every query could be
+/// answered.
+fn answer(map: &OrbitMap, queries: &[(String, String)]) -> io::Result<bool> {
+    let ancestors = Ancestors::new(map);
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut ok = true;
+    for (a, b) in queries {
+        let (from, to) = match (map.id(a), map.id(b)) {
+            (Some(from), Some(to)) => (from, to),
+            (from, _) => {
+                eprintln!("unknown body {}", if from.is_none() { a } else { b });
+                ok = false;
+                continue;
+            }
+        };
+        match (map.parent(from), map.parent(to)) {
+            (Some(from), Some(to)) => {
+                let path = ancestors.path(from, to).into_iter().map(|body| map.name(body)).collect::<Vec<_>>();
+                writeln!(out, "{} -> {}: {} ({})", a, b, path.len() - 1, path.join(" "))?;
+            }
+            _ => {
+                eprintln!("{} -> {}: {} orbits nothing", a, b, if map.parent(from).is_none() { a } else { b });
+                ok = false;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(ok)
+}
 
 fn main() {
-    let stdin = io::stdin();
-    let input = stdin.lock().lines().map(|line| line.unwrap()).collect::<Vec<String>>();
-
-    let mut orbits: HashMap<String, Vec<String>> = HashMap::new();
-    for line in input {
-        let line: Vec<_> = line.split(')').collect();
-        let a = line[0].to_string();
-        let b = line[1].to_string();
-        orbits.entry(a).or_insert_with(Vec::new).push(b);
+    let options = parse_args();
+    let input = parse::read_stdin();
+    if options.check {
+        parse::check(OrbitMap::parse(&input));
     }
+    let map = parse::or_exit(OrbitMap::parse(&input));
 
-    let mut part1 = 0;
-    let mut nodes: Vec<(&str, usize, Vec<&str>)>  = vec![("COM", 0, vec!["COM"])];
-    let mut san: HashSet<&str> = HashSet::new();
-    let mut you: HashSet<&str> = HashSet::new();
-
-    while !nodes.is_empty() {
-        let (current, depth, ref path) = nodes.pop().unwrap();
-        part1 += depth;
-        if let Some(list) = orbits.get(current) {
-            for i in list {
-                match i.as_ref() {
-                    "YOU" => you = path.iter().copied().collect::<HashSet<_>>(),
-                    "SAN" => san = path.iter().copied().collect::<HashSet<_>>(),
-                    _ => ()
-                };
-                let mut newpath = path.clone();
-                newpath.push(i);
-                nodes.push((i, depth + 1, newpath));
+    if !options.queries.is_empty() {
+        match answer(&map, &options.queries) {
+            Ok(ok) => process::exit(if ok { 0 } else { 1 }),
+            // the reader has seen all it wanted
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1)
             }
         }
     }
-    println!("Part 1: {}", part1);
 
-    let part2 = you.symmetric_difference(&san).count();
-    println!("Part 2: {}", part2);
+    let ends = map.id("YOU").zip(map.id("SAN"));
+    let output = Output::new(6, options.format);
+    output.part(1, || map.total_orbits());
+    output.part(2, || ends.map(|(you, san)| map.transfers(you, san).unwrap()));
+    let path = ends.map(|(you, san)| map.path(you, san)).unwrap_or_default();
+
+    if options.tree {
+        print!("{}", draw::tree(&map, options.depth));
+    }
+    if let Some(file) = &options.dot {
+        if let Err(e) = fs::write(file, draw::dot(&map, &path)) {
+            eprintln!("{}: {}", file, e);
+            process::exit(1);
+        }
+    }
 }
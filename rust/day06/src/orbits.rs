@@ -0,0 +1,412 @@
+//! The orbit map as a tree of bodies rooted at `COM`.
+//!
+//! Bodies live in an arena and refer to each other by `BodyId`. The map is
+//! validated when it is built, so every body has exactly one path to the root.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use aoc::parse::{self, ParseError};
+
+pub const ROOT: &str = "COM";
+
+pub type BodyId = usize;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OrbitError {
+    Syntax(ParseError),
+    MissingRoot,
+    MultipleParents { body: String, first: String, second: String },
+    Cycle { body: String },
+    /// `COM` orbits `parent` without being part of a cycle.
+    RootHasParent { parent: String },
+    /// `body` is in a tree whose root is not `COM`.
+    Detached { body: String, root: String },
+}
+
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::Syntax(e) => write!(f, "{}", e),
+            OrbitError::MissingRoot => write!(f, "nothing orbits {}", ROOT),
+            OrbitError::MultipleParents { body, first, second } => write!(f, "{} orbits both {} and {}", body, first, second),
+            OrbitError::Cycle { body } => write!(f, "{} is part of an orbit cycle", body),
+            OrbitError::RootHasParent { parent } => write!(f, "{} orbits {} but must orbit nothing", ROOT, parent),
+            OrbitError::Detached { body, root } => write!(f, "{} orbits {} which does not lead to {}", body, root, ROOT),
+        }
+    }
+}
+
+impl std::error::Error for OrbitError {}
+
+impl From<ParseError> for OrbitError {
+    fn from(e: ParseError) -> OrbitError {
+        OrbitError::Syntax(e)
+    }
+}
+
+/// The two names in an `A)B` orbit on `line`.
+fn orbit(line: usize, source: &str) -> Result<(&str, &str), ParseError> {
+    let mut fields = parse::fields(source, ')');
+    let (start, center) = fields.next().unwrap();
+    let end = source.trim_end().chars().count() + 1;
+    let (after, satellite) = fields.next().ok_or_else(|| ParseError::new(line, end, "", "`)`", source))?;
+    if fields.next().is_some() {
+        let column = source.chars().enumerate().filter(|&(_, c)| c == ')').nth(1).unwrap().0 + 1;
+        return Err(ParseError::new(line, column, ")", "a single `)`", source));
+    }
+    for &(column, name) in &[(start, center), (after, satellite)] {
+        if name.is_empty() {
+            return Err(ParseError::new(line, column, "", "a body name", source));
+        }
+    }
+    Ok((center, satellite))
+}
+
+/// Parses pairs of body names to find the transfers between, one pair per
+/// line, separated by whitespace or a comma like `YOU SAN` or `YOU,SAN`.
+pub fn queries(text: &str) -> Result<Vec<(String, String)>, ParseError> {
+    parse::lines(text).map(|(line, source)| {
+        let mut names = Vec::new();
+        let mut start = None;
+        let ends = source.char_indices().chain(std::iter::once((source.len(), ',')));
+        for (column, (i, c)) in (1..).zip(ends) {
+            match (start, c.is_whitespace() || c == ',') {
+                (None, false) => start = Some((column, i)),
+                (Some((first, from)), true) => {
+                    names.push((first, &source[from..i]));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        match names[..] {
+            [(_, a), (_, b)] => Ok((a.to_string(), b.to_string())),
+            [] => {
+                let column = source.chars().take_while(|c| c.is_whitespace()).count() + 1;
+                Err(ParseError::new(line, column, source.trim(), "two body names", source))
+            }
+            [_] => Err(ParseError::new(line, source.trim_end().chars().count() + 1, "", "a second body name", source)),
+            _ => Err(ParseError::new(line, names[2].0, names[2].1, "only two body names", source)),
+        }
+    }).collect()
+}
+
+#[derive(Debug)]
+pub struct OrbitMap {
+    names: Vec<String>,
+    index: HashMap<String, BodyId>,
+    parent: Vec<Option<BodyId>>,
+    children: Vec<Vec<BodyId>>,
+    depth: Vec<usize>,
+    subtree: Vec<usize>,
+}
+
+impl OrbitMap {
+    /// Parses one `A)B` orbit per line, skipping blank lines.
+    pub fn parse(text: &str) -> Result<OrbitMap, OrbitError> {
+        let pairs = parse::lines(text).map(|(line, source)| orbit(line, source)).collect::<Result<Vec<_>, _>>()?;
+        OrbitMap::from_pairs(pairs)
+    }
+
+    /// Builds the map from `(center, satellite)` pairs.
+    pub fn from_pairs<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(pairs: I) -> Result<OrbitMap, OrbitError> {
+        let mut map = OrbitMap {
+            names: Vec::new(),
+            index: HashMap::new(),
+            parent: Vec::new(),
+            children: Vec::new(),
+            depth: Vec::new(),
+            subtree: Vec::new(),
+        };
+        for (center, satellite) in pairs {
+            let c = map.intern(center);
+            let s = map.intern(satellite);
+            if let Some(p) = map.parent[s] {
+                return Err(OrbitError::MultipleParents {
+                    body: satellite.to_string(),
+                    first: map.names[p].clone(),
+                    second: center.to_string(),
+                });
+            }
+            map.parent[s] = Some(c);
+            map.children[c].push(s);
+        }
+        let root = map.id(ROOT).ok_or(OrbitError::MissingRoot)?;
+        // the search below only finds cycles that the root is not part of
+        if let Some(parent) = map.parent[root] {
+            let mut body = parent;
+            for _ in 0..map.len() {
+                match map.parent[body] {
+                    Some(p) if p == root => return Err(OrbitError::Cycle { body: ROOT.to_string() }),
+                    Some(p) => body = p,
+                    None => break,
+                }
+            }
+            return Err(OrbitError::RootHasParent { parent: map.names[parent].clone() });
+        }
+
+        // breadth first from the root, so parents come before their satellites
+        let mut order = vec![root];
+        let mut seen = vec![false; map.len()];
+        seen[root] = true;
+        let mut i = 0;
+        while i < order.len() {
+            for &c in &map.children[order[i]] {
+                if !seen[c] {
+                    seen[c] = true;
+                    order.push(c);
+                }
+            }
+            i += 1;
+        }
+        if let Some(lost) = (0..map.len()).find(|&b| !seen[b]) {
+            return Err(map.unreachable(lost));
+        }
+
+        map.depth = vec![0; map.len()];
+        for &b in &order[1..] {
+            map.depth[b] = map.depth[map.parent[b].unwrap()] + 1;
+        }
+        map.subtree = vec![1; map.len()];
+        for &b in order[1..].iter().rev() {
+            let p = map.parent[b].unwrap();
+            map.subtree[p] += map.subtree[b];
+        }
+        Ok(map)
+    }
+
+    fn intern(&mut self, name: &str) -> BodyId {
+        if let Some(&id) = self.index.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), id);
+        self.parent.push(None);
+        self.children.push(Vec::new());
+        id
+    }
+
+    /// Why `body` can not be reached from the root.
+    fn unreachable(&self, body: BodyId) -> OrbitError {
+        let mut visited = vec![false; self.len()];
+        let mut b = body;
+        loop {
+            if visited[b] {
+                return OrbitError::Cycle { body: self.names[b].clone() };
+            }
+            visited[b] = true;
+            match self.parent[b] {
+                Some(p) => b = p,
+                None => return OrbitError::Detached { body: self.names[body].clone(), root: self.names[b].clone() },
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn root(&self) -> BodyId {
+        self.index[ROOT]
+    }
+
+    pub fn id(&self, name: &str) -> Option<BodyId> {
+        self.index.get(name).copied()
+    }
+
+    pub fn name(&self, body: BodyId) -> &str {
+        &self.names[body]
+    }
+
+    pub fn parent(&self, body: BodyId) -> Option<BodyId> {
+        self.parent[body]
+    }
+
+    pub fn children(&self, body: BodyId) -> &[BodyId] {
+        &self.children[body]
+    }
+
+    /// Number of bodies `body` orbits, directly or indirectly.
+    pub fn depth(&self, body: BodyId) -> usize {
+        self.depth[body]
+    }
+
+    /// Number of bodies in the subtree of `body`, counting itself.
+    pub fn subtree_size(&self, body: BodyId) -> usize {
+        self.subtree[body]
+    }
+
+    /// The sum of the direct and indirect orbits of every body.
+    pub fn total_orbits(&self) -> usize {
+        self.depth.iter().sum()
+    }
+
+    /// Lowest common ancestor: the deepest body both `a` and `b` are in the
+    /// subtree of.
+    pub fn lca(&self, mut a: BodyId, mut b: BodyId) -> BodyId {
+        while self.depth[a] > self.depth[b] {
+            a = self.parent[a].unwrap();
+        }
+        while self.depth[b] > self.depth[a] {
+            b = self.parent[b].unwrap();
+        }
+        while a != b {
+            a = self.parent[a].unwrap();
+            b = self.parent[b].unwrap();
+        }
+        a
+    }
+
+    /// The bodies from `a` to `b` through their common ancestor, both ends
+    /// included.
+    pub fn path(&self, a: BodyId, b: BodyId) -> Vec<BodyId> {
+        self.path_through(a, b, self.lca(a, b))
+    }
+
+    fn path_through(&self, a: BodyId, b: BodyId, top: BodyId) -> Vec<BodyId> {
+        let climb = |mut body: BodyId| {
+            let mut path = vec![body];
+            while body != top {
+                body = self.parent[body].unwrap();
+                path.push(body);
+            }
+            path
+        };
+        let mut path = climb(a);
+        let mut down = climb(b);
+        down.pop();
+        path.extend(down.into_iter().rev());
+        path
+    }
+
+    /// Orbital transfers needed to move from what `a` orbits to what `b`
+    /// orbits, `None` if either is the root.
+    pub fn transfers(&self, a: BodyId, b: BodyId) -> Option<usize> {
+        let (a, b) = (self.parent[a]?, self.parent[b]?);
+        Some(self.depth[a] + self.depth[b] - 2 * self.depth[self.lca(a, b)])
+    }
+}
+
+/// A binary lifting table over an `OrbitMap` for answering many common
+/// ancestor queries, each in O(log depth) instead of O(depth).
+pub struct Ancestors<'a> {
+    map: &'a OrbitMap,
+    /// `up[k][b]` is the ancestor `2^k` levels above `b`, or the root.
+    up: Vec<Vec<BodyId>>,
+}
+
+impl<'a> Ancestors<'a> {
+    pub fn new(map: &'a OrbitMap) -> Ancestors<'a> {
+        let root = map.root();
+        let max_depth = map.depth.iter().copied().max().unwrap_or(0);
+        let mut up = vec![(0..map.len()).map(|b| map.parent[b].unwrap_or(root)).collect::<Vec<_>>()];
+        while 1 << up.len() <= max_depth {
+            let last = up.last().unwrap();
+            let next = last.iter().map(|&b| last[b]).collect();
+            up.push(next);
+        }
+        Ancestors { map, up }
+    }
+
+    /// The ancestor `n` levels above `body`.
+    pub fn ancestor(&self, mut body: BodyId, n: usize) -> BodyId {
+        for (k, level) in self.up.iter().enumerate() {
+            if n >> k & 1 == 1 {
+                body = level[body];
+            }
+        }
+        body
+    }
+
+    pub fn lca(&self, a: BodyId, b: BodyId) -> BodyId {
+        let (da, db) = (self.map.depth[a], self.map.depth[b]);
+        let (mut a, mut b) = if da > db { (self.ancestor(a, da - db), b) } else { (a, self.ancestor(b, db - da)) };
+        if a == b {
+            return a;
+        }
+        for level in self.up.iter().rev() {
+            if level[a] != level[b] {
+                a = level[a];
+                b = level[b];
+            }
+        }
+        self.up[0][a]
+    }
+
+    pub fn path(&self, a: BodyId, b: BodyId) -> Vec<BodyId> {
+        self.map.path_through(a, b, self.lca(a, b))
+    }
+
+    /// Same as `OrbitMap::transfers`.
+    pub fn transfers(&self, a: BodyId, b: BodyId) -> Option<usize> {
+        let (a, b) = (self.map.parent[a]?, self.map.parent[b]?);
+        Some(self.map.depth[a] + self.map.depth[b] - 2 * self.map.depth[self.lca(a, b)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN\n";
+
+    #[test]
+    fn test_queries() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        let id = |name| map.id(name).unwrap();
+        assert_eq!(map.total_orbits(), 42 + 7 + 5);
+        assert_eq!(map.depth(id("L")), 7);
+        assert_eq!(map.name(map.lca(id("YOU"), id("SAN"))), "D");
+        assert_eq!(map.transfers(id("YOU"), id("SAN")), Some(4));
+        assert_eq!(map.transfers(id("COM"), id("SAN")), None);
+        let path = map.path(id("YOU"), id("SAN")).into_iter().map(|b| map.name(b)).collect::<Vec<_>>();
+        assert_eq!(path, vec!["YOU", "K", "J", "E", "D", "I", "SAN"]);
+        assert_eq!(map.path(id("H"), id("H")), vec![id("H")]);
+        assert_eq!(map.subtree_size(id("COM")), map.len());
+        assert_eq!(map.subtree_size(id("E")), 6);
+    }
+
+    #[test]
+    fn test_parse_queries() {
+        let pairs = |list: &[(&str, &str)]| list.iter().map(|&(a, b)| (a.to_string(), b.to_string())).collect::<Vec<_>>();
+        assert_eq!(queries("YOU SAN\n\n  B,\tL \nC , D\n"), Ok(pairs(&[("YOU", "SAN"), ("B", "L"), ("C", "D")])));
+        assert_eq!(queries("A B\nYOU\n"), Err(ParseError::new(2, 4, "", "a second body name", "YOU")));
+        assert_eq!(queries("A B C\n"), Err(ParseError::new(1, 5, "C", "only two body names", "A B C")));
+        assert_eq!(queries(" ,\n"), Err(ParseError::new(1, 2, ",", "two body names", " ,")));
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        let ancestors = Ancestors::new(&map);
+        assert_eq!(map.name(ancestors.ancestor(map.id("YOU").unwrap(), 3)), "E");
+        for a in 0..map.len() {
+            for b in 0..map.len() {
+                assert_eq!(ancestors.lca(a, b), map.lca(a, b));
+                assert_eq!(ancestors.transfers(a, b), map.transfers(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let syntax = |line, column, text, expected, source| Err(OrbitError::Syntax(ParseError::new(line, column, text, expected, source)));
+        assert_eq!(OrbitMap::parse("COM)B\nB-C\n").map(|_| ()), syntax(2, 4, "", "`)`", "B-C"));
+        assert_eq!(OrbitMap::parse("COM)B\nB)C)D\n").map(|_| ()), syntax(2, 4, ")", "a single `)`", "B)C)D"));
+        assert_eq!(OrbitMap::parse("COM)B\n\nB) \n").map(|_| ()), syntax(3, 4, "", "a body name", "B) "));
+        assert_eq!(OrbitMap::parse("A)B\n").unwrap_err(), OrbitError::MissingRoot);
+        assert_eq!(OrbitMap::parse("COM)B\nA)B\n").unwrap_err(),
+                   OrbitError::MultipleParents { body: "B".to_string(), first: "COM".to_string(), second: "A".to_string() });
+        assert_eq!(OrbitMap::parse("COM)B\nC)D\nD)C\n").unwrap_err(), OrbitError::Cycle { body: "C".to_string() });
+        assert_eq!(OrbitMap::parse("COM)B\nB)COM\n").unwrap_err(), OrbitError::Cycle { body: "COM".to_string() });
+        assert_eq!(OrbitMap::parse("COM)B\nX)COM\n").unwrap_err(), OrbitError::RootHasParent { parent: "X".to_string() });
+        assert_eq!(OrbitError::RootHasParent { parent: "X".to_string() }.to_string(), "COM orbits X but must orbit nothing");
+        assert_eq!(OrbitMap::parse("COM)B\nX)Y\n").unwrap_err(), OrbitError::Detached { body: "X".to_string(), root: "X".to_string() });
+    }
+}
@@ -0,0 +1,3 @@
+//! The Amplification Circuit of day 7.
+
+pub mod amplifiers;
@@ -0,0 +1,3 @@
+//! The Space Image Format of day 8.
+
+pub mod sif;
@@ -1,28 +1,121 @@
+use std::env;
+use std::fs;
 use std::io;
-use std::io::prelude::*;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
 
+use aoc::image::{self, Picture};
+use aoc::output::{Answer, Format, Output};
+use aoc::parse;
+use day08::sif::SifImage;
 
-const WIDTH: usize = 25;
-const HEIGHT: usize = 6;
-const LAYER_SIZE: usize = WIDTH * HEIGHT;
+struct Export {
+    check: bool,
+    width: usize,
+    height: usize,
+    screen: Option<String>,
+    layers: Option<String>,
+    scale: usize,
+    hide: Option<String>,
+    layer_count: usize,
+    noise: f64,
+    seed: u64,
+    format: Format,
+}
 
-fn main() {
-    let stdin = io::stdin();
-    let input = stdin.lock().lines().next().unwrap().unwrap().chars().map(|c| c.to_digit(10).unwrap() as u8).collect::<Vec<u8>>();
-    let layers = input.chunks(LAYER_SIZE).collect::<Vec<_>>();
-    let (ones, twos) = layers.iter().min_by_key(|layer| layer.iter().filter(|&&p| p == 0).count()).unwrap().iter().fold((0, 0), |(ones, twos), p| {
-        match p {
-            1 => (ones + 1, twos),
-            2 => (ones, twos + 1),
-            _ => (ones, twos)
+fn usage() -> ! {
+    eprintln!("usage: day08 [--width W] [--height H] [--check-input] [--export IMAGE] [--export-layers DIR|ANIMATION.gif] [--scale N] [--format text|json] < input");
+    eprintln!("       day08 --hide PICTURE.pbm|PICTURE.png [--layers N] [--noise P] [--seed N] > input");
+    process::exit(2)
+}
+
+fn number<T: FromStr>(arg: Option<String>) -> T {
+    arg.and_then(|n| n.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn parse_args() -> Export {
+    let mut export = Export {
+        check: false, width: 25, height: 6, screen: None, layers: None, scale: 10,
+        hide: None, layer_count: 100, noise: 0.5, seed: 1, format: Format::Text,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--check-input" => export.check = true,
+            "--width" => export.width = number(args.next()),
+            "--height" => export.height = number(args.next()),
+            "--export" => export.screen = Some(args.next().unwrap_or_else(|| usage())),
+            "--export-layers" => export.layers = Some(args.next().unwrap_or_else(|| usage())),
+            "--scale" => export.scale = number(args.next()),
+            "--hide" => export.hide = Some(args.next().unwrap_or_else(|| usage())),
+            "--layers" => export.layer_count = number(args.next()),
+            "--noise" => export.noise = number(args.next()),
+            "--seed" => export.seed = number(args.next()),
+            "--format" => export.format = args.next().and_then(|f| Format::parse(&f)).unwrap_or_else(|| usage()),
+            _ => usage(),
         }
+    }
+    export
+}
+
+/// Writes every layer as an animated GIF, or as numbered PNGs into a directory.
+fn export_layers(image: &SifImage, path: &str, scale: usize) -> io::Result<()> {
+    let frames = (0..image.layer_count()).map(|i| Picture::from_grid(&image.layer(i))).collect::<Vec<_>>();
+    if path.ends_with(".gif") {
+        return fs::write(path, image::to_gif(&frames, scale, 20)?);
+    }
+    fs::create_dir_all(path)?;
+    for (i, frame) in frames.iter().enumerate() {
+        frame.save(Path::new(path).join(format!("layer{:03}.png", i)), scale)?;
+    }
+    Ok(())
+}
+
+fn save_exports(export: &Export, image: &SifImage) -> io::Result<()> {
+    if let Some(path) = &export.screen {
+        Picture::from_grid(&image.composite()).save(path, export.scale)?;
+    }
+    if let Some(path) = &export.layers {
+        export_layers(image, path, export.scale)?;
+    }
+    Ok(())
+}
+
+/// Prints a SIF image that composites to the picture at `path`.
+fn hide(export: &Export, path: &str) {
+    let picture = Picture::load(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1)
     });
-    println!("Part 1: {}", ones * twos);
+    match SifImage::hide(&picture, export.layer_count, export.noise, export.seed) {
+        Ok(image) => println!("{}", image.encode()),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1)
+        }
+    }
+}
 
-    let screen = (0..LAYER_SIZE).map(|i| {
-        layers.iter().map(|l| l[i]).find(|&px| px == 0 ||  px == 1).unwrap()
-    }).collect::<Vec<_>>();
+fn main() {
+    let export = parse_args();
+    if let Some(path) = &export.hide {
+        return hide(&export, path);
+    }
+    let input = parse::read_stdin();
+    if export.check {
+        parse::check(SifImage::decode(export.width, export.height, &input));
+    }
+    let image = parse::or_exit(SifImage::decode(export.width, export.height, &input));
+    let output = Output::new(8, export.format);
+    output.part(1, || image.checksum());
+    output.part(2, || {
+        let screen = image.composite();
+        Answer::picture(screen.rows().map(|row| row.iter().map(|&p| p == image::WHITE).collect()).collect())
+    });
 
-    println!("Part 2:");
-    screen.chunks(WIDTH).for_each(|line| println!("{}", line.iter().map(|&p| if p == 0 {' '} else {'█'}).collect::<String>()));
+    if let Err(e) = save_exports(&export, &image) {
+        eprintln!("export failed: {}", e);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,205 @@
+//! Space Image Format: a string of digits holding `width * height` sized
+//! layers, front layer first. When compositing, 0 is black, 1 is white and
+//! any other digit is transparent, letting the layers behind show through.
+
+use std::fmt;
+
+use aoc::grid::Grid;
+use aoc::image::{Picture, BLACK, TRANSPARENT, WHITE};
+use aoc::parse::ParseError;
+use aoc::rng::XorShift;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SifError {
+    ZeroSize,
+    /// The layers would hold more pixels than a `usize` counts.
+    TooLarge,
+    Empty,
+    /// A pixel value above 9.
+    InvalidDigit { position: usize, found: u8 },
+    Syntax(ParseError),
+    IncompleteLayer { len: usize, layer_size: usize },
+}
+
+impl fmt::Display for SifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SifError::ZeroSize => write!(f, "image width and height must be positive"),
+            SifError::TooLarge => write!(f, "image is too large"),
+            SifError::Empty => write!(f, "image has no layers"),
+            SifError::InvalidDigit { position, found } => write!(f, "invalid pixel {} at position {}", found, position + 1),
+            SifError::Syntax(e) => write!(f, "{}", e),
+            SifError::IncompleteLayer { len, layer_size } => {
+                write!(f, "{} digits do not make whole layers of {}, the last one has {}", len, layer_size, len % layer_size)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SifError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SifImage {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl SifImage {
+    /// Checks the size of `data`, which holds the digits of all layers.
+    pub fn new(width: usize, height: usize, data: Vec<u8>) -> Result<SifImage, SifError> {
+        if width == 0 || height == 0 {
+            return Err(SifError::ZeroSize);
+        }
+        let layer_size = width.checked_mul(height).ok_or(SifError::TooLarge)?;
+        if data.is_empty() {
+            return Err(SifError::Empty);
+        }
+        if !data.len().is_multiple_of(layer_size) {
+            return Err(SifError::IncompleteLayer { len: data.len(), layer_size });
+        }
+        if let Some(position) = data.iter().position(|&d| d > 9) {
+            return Err(SifError::InvalidDigit { position, found: data[position] });
+        }
+        Ok(SifImage { width, height, data })
+    }
+
+    /// An image of `layers` layers that composites to `picture`. Each opaque
+    /// pixel sits on a random layer with transparency in front of it; behind
+    /// it every pixel is a random digit with probability `noise`.
+    pub fn hide(picture: &Picture, layers: usize, noise: f64, seed: u64) -> Result<SifImage, SifError> {
+        if layers == 0 {
+            return Err(SifError::Empty);
+        }
+        let size = picture.width * picture.height;
+        let mut data = vec![TRANSPARENT; size.checked_mul(layers).ok_or(SifError::TooLarge)?];
+        let mut rng = XorShift::new(seed);
+        for (i, &px) in picture.pixels.iter().enumerate() {
+            if px >= TRANSPARENT {
+                continue;
+            }
+            let front = rng.below(layers as u64) as usize;
+            data[front * size + i] = px;
+            for layer in front + 1..layers {
+                if rng.chance(noise) {
+                    data[layer * size + i] = rng.below(3) as u8;
+                }
+            }
+        }
+        SifImage::new(picture.width, picture.height, data)
+    }
+
+    /// Parses SIF text, ignoring surrounding whitespace.
+    pub fn decode(width: usize, height: usize, text: &str) -> Result<SifImage, SifError> {
+        let skipped = text.chars().take_while(|c| c.is_whitespace()).count();
+        let data = text.trim().chars().enumerate().map(|(position, c)| {
+            c.to_digit(10).map(|d| d as u8)
+                .ok_or_else(|| SifError::Syntax(ParseError::at_offset(text, skipped + position, &c.to_string(), "a digit")))
+        }).collect::<Result<Vec<_>, _>>()?;
+        SifImage::new(width, height, data)
+    }
+
+    pub fn encode(&self) -> String {
+        self.data.iter().map(|&d| char::from(b'0' + d)).collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn layer_count(&self) -> usize {
+        self.data.len() / (self.width * self.height)
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks(self.width * self.height)
+    }
+
+    /// Layer `i` as a grid, counting from the front.
+    pub fn layer(&self, i: usize) -> Grid<u8> {
+        let layer = self.layers().nth(i).unwrap_or_else(|| panic!("there are only {} layers", self.layer_count()));
+        Grid::from_cells(self.width, self.height, layer.to_vec())
+    }
+
+    /// How many times each digit occurs in every layer.
+    pub fn histograms(&self) -> Vec<[usize; 10]> {
+        self.layers().map(|layer| {
+            let mut counts = [0; 10];
+            for &d in layer {
+                counts[d as usize] += 1;
+            }
+            counts
+        }).collect()
+    }
+
+    /// The corruption check of part 1: ones times twos on the layer with the
+    /// fewest zeros.
+    pub fn checksum(&self) -> usize {
+        let counts = self.histograms().into_iter().min_by_key(|counts| counts[0]).unwrap();
+        counts[1] * counts[2]
+    }
+
+    /// The visible image, `TRANSPARENT` wherever no layer is opaque.
+    pub fn composite(&self) -> Grid<u8> {
+        let mut screen = Grid::new(self.width, self.height, TRANSPARENT);
+        for layer in (0..self.layer_count()).rev().map(|i| self.layer(i)) {
+            for (p, &px) in layer.iter().filter(|&(_, &px)| px == BLACK || px == WHITE) {
+                screen[p] = px;
+            }
+        }
+        screen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        let image = SifImage::decode(3, 2, "123456789012\n").unwrap();
+        assert_eq!(image.layer_count(), 2);
+        assert_eq!(image.layers().collect::<Vec<_>>(), vec![&[1, 2, 3, 4, 5, 6][..], &[7, 8, 9, 0, 1, 2][..]]);
+        assert_eq!(image.histograms()[1][..3], [1, 1, 1]);
+        assert_eq!(image.checksum(), 1);
+        assert_eq!(image.encode(), "123456789012");
+    }
+
+    #[test]
+    fn test_composite() {
+        let image = SifImage::decode(2, 2, "0222112222120000").unwrap();
+        assert_eq!(image.composite().cells(), [0, 1, 1, 0]);
+        assert_eq!(image.layer(1).render(|&d| char::from(b'0' + d)), "11\n22\n");
+        let image = SifImage::decode(2, 1, "2102").unwrap();
+        assert_eq!(image.composite().cells(), [BLACK, WHITE]);
+        let image = SifImage::decode(2, 1, "2122").unwrap();
+        assert_eq!(image.composite().cells(), [TRANSPARENT, WHITE]);
+    }
+
+    #[test]
+    fn test_hide() {
+        let picture = Picture::new(4, 2, vec![BLACK, WHITE, TRANSPARENT, WHITE, BLACK, BLACK, WHITE, TRANSPARENT]);
+        for &(layers, noise) in &[(1, 0.0), (5, 0.5), (20, 1.0)] {
+            let image = SifImage::hide(&picture, layers, noise, 7).unwrap();
+            assert_eq!(image.layer_count(), layers);
+            assert_eq!(Picture::from_grid(&image.composite()), picture);
+            assert_eq!(SifImage::decode(4, 2, &image.encode()), Ok(image));
+        }
+        assert_eq!(SifImage::hide(&picture, 0, 0.0, 7), Err(SifError::Empty));
+        assert_eq!(SifImage::hide(&picture, usize::MAX, 0.0, 7), Err(SifError::TooLarge));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(SifImage::decode(3, 2, "12345"), Err(SifError::IncompleteLayer { len: 5, layer_size: 6 }));
+        assert_eq!(SifImage::decode(3, 2, "\n 1234a6"), Err(SifError::Syntax(ParseError::new(2, 6, "a", "a digit", " 1234a6"))));
+        assert_eq!(SifImage::new(1, 1, vec![12]), Err(SifError::InvalidDigit { position: 0, found: 12 }));
+        assert_eq!(SifImage::decode(3, 2, "  \n"), Err(SifError::Empty));
+        assert_eq!(SifImage::decode(0, 2, "12"), Err(SifError::ZeroSize));
+        assert_eq!(SifImage::decode(usize::MAX, 2, "12"), Err(SifError::TooLarge));
+    }
+}
@@ -0,0 +1,33 @@
+//! The BOOST program, in test mode and in sensor boost mode.
+
+use intcode::{Int, IntCodeVM};
+
+pub fn part1(starting_memory: &[Int]) -> Int {
+    let mut vm = IntCodeVM::new(starting_memory, &[1]);
+    *vm.collect_output().last().unwrap()
+}
+
+pub fn part2(starting_memory: &[Int]) -> Int {
+    let mut vm = IntCodeVM::new(starting_memory, &[2]);
+    *vm.collect_output().last().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p1_example1() {
+        let testinput = "104,1125899906842624,99";
+        let testinput = testinput.split(',').map(|n| n.parse().unwrap()).collect::<Vec<Int>>();
+        let mut vm = IntCodeVM::new(&testinput, &[]);
+        assert_eq!(vm.next_output(), Some(1125899906842624));
+    }
+    #[test]
+    fn test_p1_example2() {
+        let testinput = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let testinput = testinput.split(',').map(|n| n.parse().unwrap()).collect::<Vec<Int>>();
+        let mut vm = IntCodeVM::new(&testinput, &[]);
+        assert_eq!(vm.collect_output(), testinput);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+//! Sensor Boost, day 9.
+
+pub mod boost;
@@ -0,0 +1,3 @@
+//! The Space Police hull painting robot of day 11.
+
+pub mod robot;
@@ -0,0 +1,71 @@
+//! The hull painting robot, driven by an Intcode program.
+
+use aoc::grid::{Bounds, Direction, Point, SparseGrid};
+use intcode::memmap::MemoryMap;
+use intcode::{Int, IntCodeVM};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Robot {
+    pub at: Point,
+    pub facing: Direction,
+}
+
+impl Default for Robot {
+    fn default() -> Robot {
+        Robot::new()
+    }
+}
+
+impl Robot {
+    pub fn new() -> Robot {
+        Robot { at: Point::ORIGIN, facing: Direction::Up }
+    }
+
+    pub fn turn_and_move(&mut self, n: u8) {
+        self.facing = match n {
+            0 => self.facing.turn_left(),
+            1 => self.facing.turn_right(),
+            e => panic!("wrong turn: {}", e)
+        };
+        self.at = self.at.step(self.facing, 1);
+    }
+}
+
+pub type Hull = SparseGrid<u8>;
+pub type MoveObserver<'a> = &'a mut dyn FnMut(&Hull, &Robot);
+
+/// Runs the painting program. `on_move` sees the hull and the robot after
+/// every move, `memmap` the VM after every instruction.
+pub fn paint(starting_memory: &[Int], inp: Int, mut memmap: Option<&mut MemoryMap>,
+         mut on_move: Option<MoveObserver>) -> Hull {
+    let mut robot = Robot::new();
+    let mut vm = IntCodeVM::new(starting_memory, &[inp]);
+    if memmap.is_some() {
+        vm.track_activity();
+    }
+    let mut observe = |vm: &IntCodeVM| if let Some(map) = memmap.as_mut() { map.observe(vm) };
+    let mut map = Hull::new();
+    map.insert(Point::ORIGIN, inp as u8);
+    while let Some(color) = vm.next_output_observed(&mut observe) {
+        assert!(color < 2);
+        map.insert(robot.at, color as u8);
+        if let Some(turn) = vm.next_output_observed(&mut observe) {
+            robot.turn_and_move(turn as u8);
+            if let Some(f) = on_move.as_mut() {
+                f(&map, &robot);
+            }
+            vm.feed_input(*map.get(robot.at).unwrap_or(&0) as Int);
+        } else {
+            break;
+        }
+    }
+    map
+}
+
+/// The white panels as rows of pixels, cropped to the painted letters.
+pub fn lit_rows(map: &Hull) -> Vec<Vec<bool>> {
+    match Bounds::of(map.iter().filter(|&(_, &c)| c == 1).map(|(p, _)| p)) {
+        Some(lit) => map.crop(lit, |c| c == Some(&1)).rows().map(<[bool]>::to_vec).collect(),
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,199 @@
+//! Puzzle inputs made up at any size, in the same form as the real ones.
+//! Every generator takes its numbers from `rng`, so the same seed always
+//! gives the same input.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use aoc::rng::XorShift;
+
+/// The moves of day03 in turning order, so the opposite of `i` is `i + 2`.
+const MOVES: [char; 4] = ['U', 'R', 'D', 'L'];
+
+/// A number in `low..=high`.
+fn between(rng: &mut XorShift, low: u64, high: u64) -> u64 {
+    match (high - low).checked_add(1) {
+        Some(n) => low + rng.below(n),
+        None => rng.next_u64(),
+    }
+}
+
+fn shuffle<T>(rng: &mut XorShift, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i as u64 + 1) as usize);
+    }
+}
+
+/// day01: `count` module masses from `min` to `max`, one per line.
+pub fn masses(rng: &mut XorShift, count: usize, min: u64, max: u64) -> String {
+    assert!(min <= max, "masses from {} to {}", min, max);
+    (0..count).map(|_| format!("{}\n", between(rng, min, max))).collect()
+}
+
+/// day03: `wires` wires, one per line, of `moves` moves from 1 to
+/// `longest` long. No move turns straight back, but nothing makes sure the
+/// wires cross.
+pub fn wires(rng: &mut XorShift, wires: usize, moves: usize, longest: u64) -> String {
+    assert!(longest > 0, "moves must be at least 1 long");
+    let mut out = String::new();
+    for _ in 0..wires {
+        let mut last = None;
+        let path = (0..moves).map(|_| {
+            let dir = loop {
+                let dir = rng.below(4) as usize;
+                if last != Some((dir + 2) % 4) {
+                    break dir;
+                }
+            };
+            last = Some(dir);
+            format!("{}{}", MOVES[dir], between(rng, 1, longest))
+        }).collect::<Vec<_>>();
+        out += &path.join(",");
+        out.push('\n');
+    }
+    out
+}
+
+/// day04: a range of `digits` digit numbers like `138241-674034`.
+pub fn range(rng: &mut XorShift, digits: u32) -> String {
+    assert!((1..=19).contains(&digits), "{} digit numbers do not fit a u64", digits);
+    let smallest = if digits == 1 { 0 } else { 10_u64.pow(digits - 1) };
+    let (a, b) = (between(rng, smallest, 10_u64.pow(digits) - 1), between(rng, smallest, 10_u64.pow(digits) - 1));
+    format!("{}-{}\n", a.min(b), a.max(b))
+}
+
+/// day06: `bodies` bodies around `COM`, then `YOU` and `SAN` orbiting two
+/// of them, as `A)B` lines in random order. Each body orbits the one made
+/// just before it with probability `chain` and any earlier one otherwise,
+/// so a higher `chain` gives a deeper tree.
+pub fn orbits(rng: &mut XorShift, bodies: usize, chain: f64) -> String {
+    // long enough names that picking an unused one stays quick
+    let mut len = 3;
+    while 36_f64.powi(len) < 2.0 * bodies as f64 {
+        len += 1;
+    }
+    let mut used = ["COM", "YOU", "SAN"].iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+    let mut names = vec!["COM".to_string()];
+    let mut orbits = Vec::new();
+    for i in 1..=bodies {
+        let name = loop {
+            let name = (0..len).map(|_| std::char::from_digit(rng.below(36) as u32, 36).unwrap().to_ascii_uppercase())
+                .collect::<String>();
+            if used.insert(name.clone()) {
+                break name;
+            }
+        };
+        let center = if rng.chance(chain) { i - 1 } else { rng.below(i as u64) as usize };
+        orbits.push(format!("{}){}", names[center], name));
+        names.push(name);
+    }
+    for name in &["YOU", "SAN"] {
+        orbits.push(format!("{}){}", names[rng.below(names.len() as u64) as usize], name));
+    }
+    shuffle(rng, &mut orbits);
+    orbits.iter().map(|orbit| format!("{}\n", orbit)).collect()
+}
+
+/// day08: a SIF image of `layers` layers of `width` by `height` digits.
+/// Every pixel is transparent with probability `transparent` and black or
+/// white otherwise, except on the last layer, which has no transparency so
+/// the image always composites.
+pub fn sif(rng: &mut XorShift, width: usize, height: usize, layers: usize, transparent: f64) -> String {
+    let mut out = String::with_capacity(width * height * layers + 1);
+    for layer in 0..layers {
+        for _ in 0..width * height {
+            let digit = if layer + 1 < layers && rng.chance(transparent) { 2 } else { rng.below(2) };
+            write!(out, "{}", digit).unwrap();
+        }
+    }
+    out + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use day03::wires as day03;
+    use day04::count::{self, Counter};
+    use day04::rules::{self, PART2};
+    use day06::orbits::{Ancestors, OrbitMap};
+    use day08::sif::SifImage;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_masses_and_range() {
+        let mut rng = XorShift::new(1);
+        let masses = day01::fuel::parse(&masses(&mut rng, 50, 12, 20)).unwrap();
+        assert_eq!(masses.len(), 50);
+        assert!(masses.iter().all(|m| (BigUint::from(12_u32)..=BigUint::from(20_u32)).contains(m)));
+
+        // the counter agrees with checking every number in the range
+        let rule = rules::parse(PART2).unwrap();
+        for _ in 0..5 {
+            let (low, high) = count::range(&range(&mut rng, 4)).unwrap();
+            assert!((1000..=9999).contains(&low) && (1000..=9999).contains(&high));
+            let digits = |n: u64| n.to_string().bytes().map(|b| b - b'0').collect::<Vec<_>>();
+            let brute = (low..=high).filter(|&n| rule.check(&digits(n))).count() as u64;
+            assert_eq!(Counter::new(&*rule).count(low, high), brute);
+        }
+    }
+
+    #[test]
+    fn test_wires() {
+        // the sweep finds the same crossings as walking every step
+        let mut rng = XorShift::new(2);
+        for _ in 0..10 {
+            let text = wires(&mut rng, 2, 20, 8);
+            let walked = text.lines().map(|line| {
+                let mut at = (0_i64, 0_i64);
+                let mut seen = HashMap::new();
+                let mut steps = 0;
+                for step in line.split(',') {
+                    let (dx, dy) = match &step[..1] {
+                        "U" => (0, -1),
+                        "D" => (0, 1),
+                        "R" => (1, 0),
+                        _ => (-1, 0),
+                    };
+                    for _ in 0..step[1..].parse::<u32>().unwrap() {
+                        at = (at.0 + dx, at.1 + dy);
+                        steps += 1;
+                        seen.entry(at).or_insert(steps);
+                    }
+                }
+                seen
+            }).collect::<Vec<_>>();
+            let both = walked[0].iter()
+                .filter(|&(&p, _)| p != (0, 0))
+                .filter_map(|(p, a)| walked[1].get(p).map(|b| (p.0.abs() + p.1.abs(), a + b)))
+                .collect::<Vec<_>>();
+            let crossings = day03::crossings(&day03::parse(&text).unwrap());
+            assert_eq!(both.iter().map(|c| c.0).min(), crossings.first().map(|c| c.distance));
+            assert_eq!(both.iter().map(|c| c.1).min(), crossings.iter().map(|c| c.steps).min());
+        }
+    }
+
+    #[test]
+    fn test_orbits() {
+        let mut rng = XorShift::new(3);
+        for &chain in &[0.0, 0.5, 1.0] {
+            let map = OrbitMap::parse(&orbits(&mut rng, 200, chain)).unwrap();
+            assert_eq!(map.len(), 203);
+            let (you, san) = (map.id("YOU").unwrap(), map.id("SAN").unwrap());
+            assert_eq!(Ancestors::new(&map).transfers(you, san), map.transfers(you, san));
+        }
+        let deep = OrbitMap::parse(&orbits(&mut rng, 50, 1.0)).unwrap();
+        assert!((0..deep.len()).any(|b| deep.depth(b) == 50));
+    }
+
+    #[test]
+    fn test_sif() {
+        let mut rng = XorShift::new(4);
+        let image = SifImage::decode(7, 3, &sif(&mut rng, 7, 3, 12, 0.9)).unwrap();
+        assert_eq!(image.layer_count(), 12);
+        assert!(image.composite().cells().iter().all(|&p| p < 2));
+        assert!(image.layers().take(11).all(|layer| layer.contains(&2)));
+    }
+}
@@ -0,0 +1,3 @@
+//! Made up puzzle inputs for stress testing and benchmarks.
+
+pub mod inputs;
@@ -0,0 +1,80 @@
+use std::env;
+use std::process;
+use std::str::FromStr;
+
+use aoc::rng::XorShift;
+use generate::inputs;
+
+fn usage() -> ! {
+    eprintln!("usage: generate DAY [--seed S] [OPTION VALUE]... > input");
+    eprintln!("  day01  --count N (100) --min M (50000) --max M (150000)");
+    eprintln!("  day03  --wires N (2) --moves N (301) --longest N (1000)");
+    eprintln!("  day04  --digits N (6)");
+    eprintln!("  day06  --bodies N (1500) --chain P (0.9)");
+    eprintln!("  day08  --width W (25) --height H (6) --layers N (100) --transparent P (0.5)");
+    process::exit(2)
+}
+
+/// The `--name value` pairs after the day, each taken once it is read.
+struct Options(Vec<(String, String)>);
+
+impl Options {
+    fn get<T: FromStr>(&mut self, name: &str, default: T) -> T {
+        match self.0.iter().position(|(n, _)| n == name) {
+            Some(i) => self.0.remove(i).1.parse().unwrap_or_else(|_| usage()),
+            None => default,
+        }
+    }
+
+    fn probability(&mut self, name: &str, default: f64) -> f64 {
+        Some(self.get(name, default)).filter(|p| (0.0..=1.0).contains(p)).unwrap_or_else(|| usage())
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let day = args.next().and_then(|d| d.trim_start_matches("day").parse::<u32>().ok()).unwrap_or_else(|| usage());
+    let mut pairs = Vec::new();
+    while let Some(arg) = args.next() {
+        match (arg.strip_prefix("--"), args.next()) {
+            (Some(name), Some(value)) => pairs.push((name.to_string(), value)),
+            _ => usage(),
+        }
+    }
+    let mut options = Options(pairs);
+    let mut rng = XorShift::new(options.get("seed", 1));
+
+    let input = match day {
+        1 => {
+            let (count, min, max) = (options.get("count", 100), options.get("min", 50_000), options.get("max", 150_000));
+            if min > max {
+                usage();
+            }
+            inputs::masses(&mut rng, count, min, max)
+        }
+        3 => {
+            let (wires, moves) = (options.get("wires", 2), options.get("moves", 301));
+            let longest = Some(options.get("longest", 1000)).filter(|&n| n > 0).unwrap_or_else(|| usage());
+            inputs::wires(&mut rng, wires, moves, longest)
+        }
+        4 => {
+            let digits = Some(options.get("digits", 6)).filter(|d| (1..=19).contains(d)).unwrap_or_else(|| usage());
+            inputs::range(&mut rng, digits)
+        }
+        6 => inputs::orbits(&mut rng, options.get("bodies", 1500), options.probability("chain", 0.9)),
+        8 => {
+            let size = (options.get("width", 25_usize), options.get("height", 6), options.get("layers", 100));
+            // the digits and a newline must fit in one string
+            let digits = size.0.checked_mul(size.1).and_then(|n| n.checked_mul(size.2)).and_then(|n| n.checked_add(1));
+            if size.0 == 0 || size.1 == 0 || size.2 == 0 || digits.is_none() {
+                usage();
+            }
+            inputs::sif(&mut rng, size.0, size.1, size.2, options.probability("transparent", 0.5))
+        }
+        _ => usage(),
+    };
+    if !options.0.is_empty() {
+        usage();
+    }
+    print!("{}", input);
+}
@@ -0,0 +1,49 @@
+/* C interface to the Intcode VM, implemented in src/ffi.rs. */
+
+#ifndef INTCODE_H
+#define INTCODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define INTCODE_OK                  0
+#define INTCODE_OUTPUT              1
+#define INTCODE_NEED_INPUT          2
+#define INTCODE_HALTED              3
+#define INTCODE_ERR_NULL           -1
+#define INTCODE_ERR_UNKNOWN_OPCODE -2
+#define INTCODE_ERR_BAD_ADDRESS    -3
+#define INTCODE_ERR_IMMEDIATE_DEST -4
+#define INTCODE_ERR_OVERFLOW       -5
+#define INTCODE_ERR_PANIC          -6
+#define INTCODE_ERR_MEMORY_LIMIT   -7
+
+typedef struct IntCodeVM IntCodeVM;
+
+/* Copies `len` cells of `program`; returns NULL on failure. */
+IntCodeVM *intcode_vm_new(const int64_t *program, size_t len);
+void intcode_vm_free(IntCodeVM *vm);
+
+int intcode_vm_push_input(IntCodeVM *vm, int64_t value);
+
+/* Both store the value in `out` (if not NULL) when returning INTCODE_OUTPUT.
+ * intcode_vm_step returns INTCODE_OK after an ordinary instruction, while
+ * intcode_vm_run keeps going until output, input needed, halt or error. */
+int intcode_vm_step(IntCodeVM *vm, int64_t *out);
+int intcode_vm_run(IntCodeVM *vm, int64_t *out);
+
+/* Memory grows on demand; unwritten cells read as 0. Addresses past the
+ * memory limit give INTCODE_ERR_MEMORY_LIMIT. */
+int intcode_vm_read_memory(const IntCodeVM *vm, size_t addr, int64_t *value);
+int intcode_vm_write_memory(IntCodeVM *vm, size_t addr, int64_t value);
+size_t intcode_vm_memory_size(const IntCodeVM *vm);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,169 @@
+//! C ABI for `IntCodeVM`, declared in `include/intcode.h`.
+//!
+//! Every function returns one of the `INTCODE_*` status codes instead of
+//! panicking; a panic that slips through anyway is caught and reported as
+//! `INTCODE_ERR_PANIC`.
+
+use std::os::raw::c_int;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use crate::vm::{Fault, Int, IntCodeVM, Step};
+
+pub const INTCODE_OK: c_int = 0;
+pub const INTCODE_OUTPUT: c_int = 1;
+pub const INTCODE_NEED_INPUT: c_int = 2;
+pub const INTCODE_HALTED: c_int = 3;
+pub const INTCODE_ERR_NULL: c_int = -1;
+pub const INTCODE_ERR_UNKNOWN_OPCODE: c_int = -2;
+pub const INTCODE_ERR_BAD_ADDRESS: c_int = -3;
+pub const INTCODE_ERR_IMMEDIATE_DEST: c_int = -4;
+pub const INTCODE_ERR_OVERFLOW: c_int = -5;
+pub const INTCODE_ERR_PANIC: c_int = -6;
+pub const INTCODE_ERR_MEMORY_LIMIT: c_int = -7;
+
+fn fault_code(fault: Fault) -> c_int {
+    match fault {
+        Fault::UnknownOpcode { .. } => INTCODE_ERR_UNKNOWN_OPCODE,
+        Fault::AddressOutOfRange { .. } => INTCODE_ERR_BAD_ADDRESS,
+        Fault::MemoryLimit { .. } => INTCODE_ERR_MEMORY_LIMIT,
+        Fault::ImmediateDestination { .. } => INTCODE_ERR_IMMEDIATE_DEST,
+        Fault::Overflow { .. } => INTCODE_ERR_OVERFLOW,
+    }
+}
+
+fn guard<F: FnOnce() -> c_int>(f: F) -> c_int {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(INTCODE_ERR_PANIC)
+}
+
+/// Runs `f` on the VM behind `vm`, or returns `INTCODE_ERR_NULL`.
+unsafe fn with_vm<F: FnOnce(&mut IntCodeVM) -> c_int>(vm: *mut IntCodeVM, f: F) -> c_int {
+    match vm.as_mut() {
+        Some(vm) => guard(|| f(vm)),
+        None => INTCODE_ERR_NULL,
+    }
+}
+
+fn report(step: Result<Step, Fault>, out: *mut Int) -> c_int {
+    match step {
+        Ok(Step::Ran) => INTCODE_OK,
+        Ok(Step::Output(value)) => {
+            if !out.is_null() {
+                unsafe { *out = value };
+            }
+            INTCODE_OUTPUT
+        }
+        Ok(Step::NeedInput) => INTCODE_NEED_INPUT,
+        Ok(Step::Halted) => INTCODE_HALTED,
+        Err(fault) => fault_code(fault),
+    }
+}
+
+/// Creates a VM running a copy of `program`. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `program` must point to `len` readable integers.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_new(program: *const Int, len: usize) -> *mut IntCodeVM {
+    if program.is_null() {
+        return ptr::null_mut();
+    }
+    let program = slice::from_raw_parts(program, len);
+    catch_unwind(|| Box::into_raw(Box::new(IntCodeVM::new(program, &[])))).unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+///
+/// `vm` must come from `intcode_vm_new` and not have been freed, or be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_free(vm: *mut IntCodeVM) {
+    if !vm.is_null() {
+        drop(Box::from_raw(vm));
+    }
+}
+
+/// Appends `value` to the VM's input queue.
+///
+/// # Safety
+///
+/// `vm` must be a live VM from `intcode_vm_new`.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_push_input(vm: *mut IntCodeVM, value: Int) -> c_int {
+    with_vm(vm, |vm| {
+        vm.feed_input(value);
+        INTCODE_OK
+    })
+}
+
+/// Executes one instruction. Returns `INTCODE_OK`, or `INTCODE_OUTPUT` with
+/// the value stored in `out` (when not NULL), `INTCODE_NEED_INPUT`,
+/// `INTCODE_HALTED` or an error.
+///
+/// # Safety
+///
+/// `vm` must be a live VM from `intcode_vm_new`, `out` writable or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_step(vm: *mut IntCodeVM, out: *mut Int) -> c_int {
+    with_vm(vm, |vm| report(vm.step(), out))
+}
+
+/// Runs until the VM outputs a value, needs input, halts or faults; returns
+/// the same codes as `intcode_vm_step` except `INTCODE_OK`.
+///
+/// # Safety
+///
+/// `vm` must be a live VM from `intcode_vm_new`, `out` writable or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_run(vm: *mut IntCodeVM, out: *mut Int) -> c_int {
+    with_vm(vm, |vm| loop {
+        match vm.step() {
+            Ok(Step::Ran) => (),
+            step => return report(step, out),
+        }
+    })
+}
+
+/// Reads one memory cell. Cells past the allocated memory read as 0 up to
+/// the memory limit.
+///
+/// # Safety
+///
+/// `vm` must be a live VM from `intcode_vm_new`, `value` writable.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_read_memory(vm: *const IntCodeVM, addr: usize, value: *mut Int) -> c_int {
+    if value.is_null() {
+        return INTCODE_ERR_NULL;
+    }
+    with_vm(vm as *mut IntCodeVM, |vm| match vm.read(addr) {
+        Ok(v) => {
+            *value = v;
+            INTCODE_OK
+        }
+        Err(fault) => fault_code(fault),
+    })
+}
+
+/// Writes one memory cell, growing memory if needed.
+///
+/// # Safety
+///
+/// `vm` must be a live VM from `intcode_vm_new`.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_write_memory(vm: *mut IntCodeVM, addr: usize, value: Int) -> c_int {
+    with_vm(vm, |vm| match vm.write(addr, value) {
+        Ok(()) => INTCODE_OK,
+        Err(fault) => fault_code(fault),
+    })
+}
+
+/// Number of memory cells allocated so far, 0 for a NULL VM.
+///
+/// # Safety
+///
+/// `vm` must be a live VM from `intcode_vm_new` or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_vm_memory_size(vm: *const IntCodeVM) -> usize {
+    vm.as_ref().map_or(0, |vm| vm.memory().len())
+}
@@ -0,0 +1,171 @@
+//! Running many untrusted programs side by side.
+//!
+//! Each job gets its own `IntCodeVM` with a memory limit, which the outputs
+//! it has printed count against, and an instruction budget, and runs on one of a fixed number of worker threads. A VM that
+//! faults, runs out of budget or panics only ends its own job; everything
+//! else keeps running and every job gets a `Report`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::vm::{Fault, Int, IntCodeVM, Step};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Cells of memory plus outputs kept for the report.
+    pub memory_cells: usize,
+    pub instructions: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub name: String,
+    pub program: Vec<Int>,
+    pub inputs: Vec<Int>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Halted,
+    /// Asked for input after all of the job's inputs were consumed.
+    StarvedForInput,
+    OutOfInstructions,
+    /// Printed more outputs than the memory limit leaves room for.
+    TooMuchOutput,
+    Crashed(Fault),
+    Panicked(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Halted => write!(f, "halted"),
+            Outcome::StarvedForInput => write!(f, "starved for input"),
+            Outcome::OutOfInstructions => write!(f, "out of instructions"),
+            Outcome::TooMuchOutput => write!(f, "too much output"),
+            Outcome::Crashed(fault) => write!(f, "crashed: {}", fault),
+            Outcome::Panicked(msg) => write!(f, "panicked: {}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub name: String,
+    pub outcome: Outcome,
+    pub instructions: u64,
+    pub memory_cells: usize,
+    pub outputs: Vec<Int>,
+    pub elapsed: Duration,
+}
+
+pub struct Host {
+    workers: usize,
+    limits: Limits,
+}
+
+impl Host {
+    pub fn new(workers: usize, limits: Limits) -> Host {
+        Host { workers: workers.max(1), limits }
+    }
+
+    /// Runs every job to completion and returns their reports in job order.
+    pub fn run(&self, jobs: Vec<Job>) -> Vec<Report> {
+        let count = jobs.len();
+        let queue = Mutex::new(jobs.into_iter().enumerate().collect::<VecDeque<_>>());
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(count) {
+                let tx = tx.clone();
+                let queue = &queue;
+                scope.spawn(move || loop {
+                    let next = queue.lock().unwrap().pop_front();
+                    match next {
+                        Some((i, job)) => tx.send((i, run_job(job, self.limits))).unwrap(),
+                        None => break,
+                    }
+                });
+            }
+        });
+        drop(tx);
+        let mut reports = rx.into_iter().collect::<Vec<_>>();
+        reports.sort_by_key(|&(i, _)| i);
+        reports.into_iter().map(|(_, report)| report).collect()
+    }
+}
+
+fn run_job(job: Job, limits: Limits) -> Report {
+    let start = Instant::now();
+    let mut vm = IntCodeVM::new(&job.program, &job.inputs);
+    vm.set_memory_limit(limits.memory_cells);
+    let mut outputs = Vec::new();
+    let outcome = catch_unwind(AssertUnwindSafe(|| loop {
+        if vm.instructions() >= limits.instructions {
+            return Outcome::OutOfInstructions;
+        }
+        match vm.step() {
+            Ok(Step::Ran) => (),
+            Ok(Step::Output(_)) if vm.memory().len() + outputs.len() >= limits.memory_cells => {
+                return Outcome::TooMuchOutput;
+            }
+            Ok(Step::Output(n)) => outputs.push(n),
+            Ok(Step::NeedInput) => return Outcome::StarvedForInput,
+            Ok(Step::Halted) => return Outcome::Halted,
+            Err(fault) => return Outcome::Crashed(fault),
+        }
+    }));
+    let outcome = outcome.unwrap_or_else(|payload| {
+        let msg = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Outcome::Panicked(msg)
+    });
+    Report {
+        name: job.name,
+        outcome,
+        instructions: vm.instructions(),
+        memory_cells: vm.memory().len(),
+        outputs,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(name: &str, program: &[Int], inputs: &[Int]) -> Job {
+        Job { name: name.to_owned(), program: program.to_vec(), inputs: inputs.to_vec() }
+    }
+
+    #[test]
+    fn test_isolation() {
+        let host = Host::new(2, Limits { memory_cells: 1000, instructions: 10_000 });
+        let reports = host.run(vec![
+            job("echo", &[3, 0, 4, 0, 99], &[7]),
+            job("spin", &[1105, 1, 0], &[]),
+            job("hog", &[1101, 1, 1, 5000, 99], &[]),
+            job("hungry", &[3, 0, 3, 0, 99], &[1]),
+            job("bad", &[42], &[]),
+            job("chatty", &[104, 1, 1105, 1, 0], &[]),
+        ]);
+        let outcomes = reports.iter().map(|r| r.outcome.clone()).collect::<Vec<_>>();
+        assert_eq!(outcomes, vec![
+            Outcome::Halted,
+            Outcome::OutOfInstructions,
+            Outcome::Crashed(Fault::MemoryLimit { pc: 0, addr: 5000 }),
+            Outcome::StarvedForInput,
+            Outcome::Crashed(Fault::UnknownOpcode { pc: 0, opcode: 42 }),
+            Outcome::TooMuchOutput,
+        ]);
+        assert_eq!(reports[0].outputs, vec![7]);
+        assert_eq!(reports[1].instructions, 10_000);
+        assert_eq!(reports[2].memory_cells, 5);
+        assert_eq!(reports[5].outputs.len(), 1000 - 5);
+    }
+}
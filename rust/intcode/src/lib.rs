@@ -0,0 +1,10 @@
+//! Shared Intcode tooling used by the Intcode days (2, 5, 7, 9 and 11).
+
+pub mod ffi;
+pub mod host;
+pub mod loader;
+pub mod memmap;
+pub mod server;
+pub mod vm;
+
+pub use vm::{Int, IntCodeVM, Step};
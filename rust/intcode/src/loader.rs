@@ -0,0 +1,280 @@
+//! Reading Intcode programs from the formats we keep them in.
+//!
+//! Text programs are integers separated by commas and/or whitespace, spread
+//! over any number of lines, where `#` starts a comment running to the end of
+//! the line. The binary format is `VARINT_MAGIC` followed by one zigzag LEB128
+//! varint per memory cell. Either kind may be gzip compressed; `load_bytes`
+//! works out which one it was given.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+use aoc::parse::ParseError;
+use flate2::read::MultiGzDecoder;
+
+pub const VARINT_MAGIC: &[u8; 4] = b"ICV1";
+const GZIP_MAGIC: &[u8; 2] = &[0x1f, 0x8b];
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    InvalidUtf8 { offset: usize },
+    InvalidNumber { line: usize, column: usize, text: String },
+    MissingNumber { line: usize, column: usize },
+    TruncatedVarint { offset: usize },
+    VarintOverflow { offset: usize },
+    Empty,
+    /// A cell that does not fit the type the program is run with.
+    Cell(ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LoadError::*;
+        match self {
+            Io(e) => write!(f, "cannot read program: {}", e),
+            InvalidUtf8 { offset } => write!(f, "program is neither text nor binary (invalid UTF-8 at byte {})", offset),
+            InvalidNumber { line, column, text } => write!(f, "line {}, column {}: invalid number `{}`", line, column, text),
+            MissingNumber { line, column } => write!(f, "line {}, column {}: expected a number before `,`", line, column),
+            TruncatedVarint { offset } => write!(f, "truncated varint at byte {}", offset),
+            VarintOverflow { offset } => write!(f, "varint at byte {} does not fit in 64 bits", offset),
+            Empty => write!(f, "program is empty"),
+            Cell(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<i64>, LoadError> {
+    load_reader(File::open(path)?)
+}
+
+pub fn load_reader<R: Read>(mut reader: R) -> Result<Vec<i64>, LoadError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    load_bytes(&bytes)
+}
+
+/// Loads a program whose cells must all convert to `T`, like the addresses
+/// of day02. The first cell that does not is reported where it is in the
+/// program text, binary programs are shown as comma separated text.
+pub fn load_cells<T: TryFrom<i64>, R: Read>(mut reader: R, expected: &str) -> Result<Vec<T>, LoadError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let program = load_bytes(&bytes)?;
+    program.iter().enumerate()
+        .map(|(i, &n)| T::try_from(n).map_err(|_| LoadError::Cell(cell_error(&bytes, &program, i, expected))))
+        .collect()
+}
+
+/// The program as it was written, `None` for a binary one.
+fn source_text(bytes: &[u8]) -> Option<String> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        let mut inflated = Vec::new();
+        MultiGzDecoder::new(bytes).read_to_end(&mut inflated).ok()?;
+        return source_text(&inflated);
+    }
+    if bytes.starts_with(VARINT_MAGIC) {
+        return None;
+    }
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+fn cell_error(bytes: &[u8], program: &[i64], index: usize, expected: &str) -> ParseError {
+    let text = source_text(bytes)
+        .unwrap_or_else(|| program.iter().map(i64::to_string).collect::<Vec<_>>().join(","));
+    let mut seen = 0;
+    for (lineno, line) in text.lines().enumerate() {
+        let code = line.split('#').next().unwrap().chars().collect::<Vec<_>>();
+        let mut start = None;
+        for (i, &c) in code.iter().chain(Some(&' ')).enumerate() {
+            if c != ',' && !c.is_whitespace() {
+                start = start.or(Some(i));
+                continue;
+            }
+            if let Some(start) = start.take() {
+                if seen == index {
+                    let token = code[start..i].iter().collect::<String>();
+                    return ParseError::new(lineno + 1, start + 1, &token, expected, line);
+                }
+                seen += 1;
+            }
+        }
+    }
+    unreachable!("cell {} is not in the program text", index)
+}
+
+/// Decodes a program in any supported format, detected from its first bytes.
+pub fn load_bytes(bytes: &[u8]) -> Result<Vec<i64>, LoadError> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        let mut inflated = Vec::new();
+        MultiGzDecoder::new(bytes).read_to_end(&mut inflated)?;
+        return load_bytes(&inflated);
+    }
+    if bytes.starts_with(VARINT_MAGIC) {
+        return decode_varint(bytes);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => parse_text(text),
+        Err(e) => Err(LoadError::InvalidUtf8 { offset: e.valid_up_to() }),
+    }
+}
+
+pub fn parse_text(text: &str) -> Result<Vec<i64>, LoadError> {
+    let mut program = Vec::new();
+    // true once a comma has been seen since the last number, so that `1,,2`
+    // and a leading comma are rejected while a trailing one is tolerated
+    let mut pending_comma = true;
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            if c == ',' {
+                if pending_comma {
+                    return Err(LoadError::MissingNumber { line: lineno + 1, column: column(line, start) });
+                }
+                pending_comma = true;
+                continue;
+            }
+            let mut end = line.len();
+            while let Some(&(i, c)) = chars.peek() {
+                if c == ',' || c.is_whitespace() {
+                    end = i;
+                    break;
+                }
+                chars.next();
+            }
+            let token = &line[start..end];
+            match token.parse() {
+                Ok(n) => program.push(n),
+                Err(_) => return Err(LoadError::InvalidNumber {
+                    line: lineno + 1,
+                    column: column(line, start),
+                    text: token.to_owned()
+                })
+            }
+            pending_comma = false;
+        }
+    }
+    if program.is_empty() {
+        return Err(LoadError::Empty);
+    }
+    Ok(program)
+}
+
+fn column(line: &str, byte_offset: usize) -> usize {
+    line[..byte_offset].chars().count() + 1
+}
+
+pub fn encode_varint(program: &[i64]) -> Vec<u8> {
+    let mut out = VARINT_MAGIC.to_vec();
+    for &n in program {
+        let mut zigzag = ((n << 1) ^ (n >> 63)) as u64;
+        while zigzag >= 0x80 {
+            out.push(zigzag as u8 | 0x80);
+            zigzag >>= 7;
+        }
+        out.push(zigzag as u8);
+    }
+    out
+}
+
+pub fn decode_varint(bytes: &[u8]) -> Result<Vec<i64>, LoadError> {
+    let mut program = Vec::new();
+    let mut offset = VARINT_MAGIC.len();
+    while offset < bytes.len() {
+        let start = offset;
+        let mut zigzag: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = *bytes.get(offset).ok_or(LoadError::TruncatedVarint { offset: start })?;
+            offset += 1;
+            if shift == 63 && byte > 1 {
+                return Err(LoadError::VarintOverflow { offset: start });
+            }
+            zigzag |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        program.push((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+    }
+    if program.is_empty() {
+        return Err(LoadError::Empty);
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn test_text_formats() {
+        assert_eq!(parse_text("1,0,0,3,99\n").unwrap(), vec![1, 0, 0, 3, 99]);
+        assert_eq!(parse_text("  1, -2\n3 4,\n\n5").unwrap(), vec![1, -2, 3, 4, 5]);
+        assert_eq!(parse_text("# header\n104,7, # out\n99 # halt\n").unwrap(), vec![104, 7, 99]);
+    }
+
+    #[test]
+    fn test_text_errors() {
+        match parse_text("1,2,\n3,x4,5") {
+            Err(LoadError::InvalidNumber { line: 2, column: 3, text }) => assert_eq!(text, "x4"),
+            e => panic!("unexpected {:?}", e)
+        }
+        match parse_text("1,2,,3") {
+            Err(LoadError::MissingNumber { line: 1, column: 5 }) => (),
+            e => panic!("unexpected {:?}", e)
+        }
+        assert!(matches!(parse_text("# nothing\n"), Err(LoadError::Empty)));
+    }
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(load_cells::<usize, _>(&b"1,2 # 3,-4\n5"[..], "a cell").unwrap(), vec![1, 2, 5]);
+        match load_cells::<usize, _>(&b"1,2,\n 5, -3,4"[..], "a cell that is not negative") {
+            Err(LoadError::Cell(e)) => assert_eq!(e, ParseError::new(2, 5, "-3", "a cell that is not negative", " 5, -3,4")),
+            e => panic!("unexpected {:?}", e),
+        }
+        match load_cells::<u8, _>(&encode_varint(&[1, 300])[..], "a byte") {
+            Err(LoadError::Cell(e)) => assert_eq!(e, ParseError::new(1, 3, "300", "a byte", "1,300")),
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_varint_roundtrip() {
+        let program = vec![109, 1, 204, -1, 0, i64::MAX, i64::MIN, 1125899906842624];
+        let encoded = encode_varint(&program);
+        assert_eq!(load_bytes(&encoded).unwrap(), program);
+        assert!(matches!(load_bytes(&encoded[..encoded.len() - 1]), Err(LoadError::TruncatedVarint { .. })));
+    }
+
+    #[test]
+    fn test_gzip() {
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(b"3,0,4,0,99\n").unwrap();
+        assert_eq!(load_bytes(&gz.finish().unwrap()).unwrap(), vec![3, 0, 4, 0, 99]);
+
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&encode_varint(&[4, 3, 99])).unwrap();
+        assert_eq!(load_bytes(&gz.finish().unwrap()).unwrap(), vec![4, 3, 99]);
+    }
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::io;
+use std::process;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use intcode::host::{Host, Job, Limits};
+use intcode::loader;
+use intcode::memmap::MemoryMap;
+use intcode::server;
+use intcode::vm::{IntCodeVM, Step, DEFAULT_MEMORY_LIMIT};
+
+const USAGE: &str = "usage: intcode memmap [--input N]... [--every STEPS] [--delay MS] [--columns N] PROGRAM
+       intcode host [--input N]... [--workers N] [--max-memory CELLS] [--max-instructions N] PROGRAM...
+       intcode serve (--socket PATH | --stdio)";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
+
+fn number<T: FromStr>(arg: Option<String>) -> T {
+    arg.and_then(|v| v.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn memmap(mut args: impl Iterator<Item = String>) {
+    let mut inputs = Vec::new();
+    let mut every = 1;
+    let mut delay = 20;
+    let mut columns = 128;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--input" => inputs.push(number(args.next())),
+            "--every" => every = number(args.next()),
+            "--delay" => delay = number(args.next()),
+            "--columns" => columns = Some(number(args.next())).filter(|&n| n > 0).unwrap_or_else(|| usage()),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage()
+        }
+    }
+    let program = loader::load_file(path.unwrap_or_else(|| usage())).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1)
+    });
+
+    let mut vm = IntCodeVM::new(&program, &[]);
+    for i in inputs {
+        vm.feed_input(i);
+    }
+    vm.track_activity();
+    let mut map = MemoryMap::new(columns, every, Duration::from_millis(delay));
+    let mut outputs = Vec::new();
+    loop {
+        let step = vm.step();
+        map.observe(&vm);
+        match step {
+            Ok(Step::Ran) => (),
+            Ok(Step::Output(n)) => outputs.push(n),
+            Ok(Step::Halted) => break,
+            Ok(Step::NeedInput) => {
+                println!("program is waiting for input, pass more with --input");
+                break;
+            }
+            Err(fault) => {
+                println!("program crashed: {}", fault);
+                break;
+            }
+        }
+    }
+    map.draw(&vm);
+    println!("outputs: {:?}", outputs);
+}
+
+fn host(mut args: impl Iterator<Item = String>) {
+    let mut inputs = Vec::new();
+    let mut workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut limits = Limits { memory_cells: DEFAULT_MEMORY_LIMIT, instructions: 100_000_000 };
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--input" => inputs.push(number(args.next())),
+            "--workers" => workers = number(args.next()),
+            "--max-memory" => limits.memory_cells = number(args.next()),
+            "--max-instructions" => limits.instructions = number(args.next()),
+            _ if !arg.starts_with("--") => paths.push(arg),
+            _ => usage()
+        }
+    }
+    if paths.is_empty() {
+        usage();
+    }
+    let jobs = paths.into_iter().map(|path| {
+        let program = loader::load_file(&path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path, e);
+            process::exit(1)
+        });
+        Job { name: path, program, inputs: inputs.clone() }
+    }).collect();
+
+    println!("{:<30} {:>12} {:>10} {:>10} {:>8}  outcome", "program", "instructions", "memory", "ms", "outputs");
+    for report in Host::new(workers, limits).run(jobs) {
+        println!("{:<30} {:>12} {:>10} {:>10} {:>8}  {}", report.name, report.instructions, report.memory_cells,
+                 report.elapsed.as_millis(), report.outputs.len(), report.outcome);
+    }
+}
+
+fn serve(mut args: impl Iterator<Item = String>) {
+    let result = match (args.next().as_deref(), args.next()) {
+        (Some("--socket"), Some(path)) => server::serve_socket(path),
+        (Some("--stdio"), None) => {
+            let stdin = io::stdin();
+            server::serve_stream(stdin.lock(), io::stdout())
+        }
+        _ => usage()
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("memmap") => memmap(args),
+        Some("host") => host(args),
+        Some("serve") => serve(args),
+        _ => usage()
+    }
+}
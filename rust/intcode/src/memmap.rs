@@ -0,0 +1,115 @@
+//! Live terminal heat map of a running VM's memory.
+//!
+//! Every character shows two memory cells stacked with the `▀` half block,
+//! coloured by how often they were written (red), read (green) and executed
+//! (blue). The cell under the pc is drawn white and the one at the relative
+//! base yellow. Needs a terminal with 24-bit colour.
+
+use std::fmt::Write as _;
+use std::io;
+use std::io::prelude::*;
+use std::thread;
+use std::time::Duration;
+
+use crate::vm::{Activity, IntCodeVM};
+
+const PC_COLOR: (u8, u8, u8) = (255, 255, 255);
+const RB_COLOR: (u8, u8, u8) = (255, 220, 0);
+const IDLE_COLOR: (u8, u8, u8) = (24, 24, 24);
+
+pub struct MemoryMap {
+    columns: usize,
+    every: usize,
+    delay: Duration,
+    steps: usize,
+}
+
+impl MemoryMap {
+    pub fn new(columns: usize, every: usize, delay: Duration) -> MemoryMap {
+        MemoryMap { columns, every: every.max(1), delay, steps: 0 }
+    }
+
+    /// Counts a step of `vm` and redraws the screen every `every` steps.
+    pub fn observe(&mut self, vm: &IntCodeVM) {
+        self.steps += 1;
+        if self.steps.is_multiple_of(self.every) {
+            self.draw(vm);
+            thread::sleep(self.delay);
+        }
+    }
+
+    pub fn draw(&self, vm: &IntCodeVM) {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if self.steps <= self.every {
+            write!(stdout, "\x1b[2J").unwrap();
+        }
+        write!(stdout, "\x1b[H{}", self.render(vm)).unwrap();
+        stdout.flush().unwrap();
+    }
+
+    pub fn render(&self, vm: &IntCodeVM) -> String {
+        let activity = vm.activity().expect("activity tracking is not enabled");
+        let used = used_len(vm, activity);
+        let rows = used.div_ceil(self.columns);
+        let mut out = String::new();
+        writeln!(out, "\x1b[0mstep {:>10}   pc {:>6}   rb {:>6}   used {:>6}\x1b[K",
+                 self.steps, vm.pc(), vm.relative_base(), used).unwrap();
+        for row in (0..rows).step_by(2) {
+            for col in 0..self.columns {
+                let (tr, tg, tb) = self.cell_color(vm, activity, row * self.columns + col);
+                let (br, bg, bb) = self.cell_color(vm, activity, (row + 1) * self.columns + col);
+                write!(out, "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀", tr, tg, tb, br, bg, bb).unwrap();
+            }
+            writeln!(out, "\x1b[0m").unwrap();
+        }
+        out
+    }
+
+    fn cell_color(&self, vm: &IntCodeVM, activity: &Activity, addr: usize) -> (u8, u8, u8) {
+        if addr == vm.pc() {
+            return PC_COLOR;
+        }
+        if addr as i64 == vm.relative_base() {
+            return RB_COLOR;
+        }
+        if addr >= activity.executes.len() {
+            return (0, 0, 0);
+        }
+        let heat = |count: u32| if count == 0 { 0 } else { (64.0 + 24.0 * (count as f64).ln()).min(255.0) as u8 };
+        match (heat(activity.writes[addr]), heat(activity.reads[addr]), heat(activity.executes[addr])) {
+            (0, 0, 0) => IDLE_COLOR,
+            color => color,
+        }
+    }
+}
+
+/// Length of memory worth drawing: everything up to the last cell that is
+/// non-zero or has been touched, so the unused tail is not drawn.
+fn used_len(vm: &IntCodeVM, activity: &Activity) -> usize {
+    let mem = vm.memory();
+    let len = mem.len().max(activity.executes.len());
+    let last = (0..len).rev().find(|&i| {
+        let touched = |counts: &[u32]| counts.get(i).is_some_and(|&c| c > 0);
+        mem.get(i).is_some_and(|&v| v != 0)
+            || touched(&activity.reads) || touched(&activity.writes) || touched(&activity.executes)
+    });
+    last.map_or(0, |i| i + 1).max(vm.pc() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_size() {
+        let mut vm = IntCodeVM::new(&[1101, 2, 3, 7, 4, 7, 99, 0], &[]);
+        vm.track_activity();
+        vm.collect_output();
+        let map = MemoryMap::new(4, 1, Duration::from_millis(0));
+        let frame = map.render(&vm);
+        // header plus 8 cells in 4 columns, two rows per line
+        assert_eq!(frame.lines().count(), 2);
+        assert_eq!(frame.matches('▀').count(), 4);
+    }
+}
@@ -0,0 +1,366 @@
+//! Line-delimited JSON-RPC 2.0 interface for driving VMs from other tools.
+//!
+//! Every request is one JSON object on its own line and gets exactly one
+//! response line, except notifications: requests without an `id` are carried
+//! out but not answered. Programs and VMs are referred to by integer ids handed out
+//! by `load_program` and `create_vm`/`snapshot`. Each connection gets its own
+//! `Session`, so ids are not shared between clients.
+//!
+//! Methods and their params:
+//!
+//! - `load_program {source}` -> `{program, length}`, the program text itself;
+//!   the server never reads files for a client
+//! - `create_vm {program, inputs?}` -> `{vm}`
+//! - `send_input {vm, values}` -> `{queued}`
+//! - `run {vm, max_instructions?}` -> `{event: "output", value} | {event: "need_input" | "halted" | "budget_exhausted"}`
+//! - `read_memory {vm, address, length?}` -> `{values}`, at most `MAX_READ` cells
+//! - `snapshot {vm}` -> `{vm}`, an independent copy of the VM
+//! - `destroy {vm}` -> `{}`
+//!
+//! `run` executes at most `max_instructions`, `DEFAULT_BUDGET` if not given,
+//! so a program that never stops can not tie up a session. After
+//! `budget_exhausted` another `run` carries on where it left off.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::thread;
+
+use serde_json::{json, Map, Value};
+
+use crate::loader;
+use crate::vm::{Int, IntCodeVM, Step};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const VM_FAULT: i64 = -32000;
+const UNKNOWN_ID: i64 = -32001;
+
+const MAX_READ: u64 = 65536;
+pub const DEFAULT_BUDGET: u64 = 10_000_000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+fn error(code: i64, message: impl Into<String>) -> RpcError {
+    RpcError { code, message: message.into() }
+}
+
+#[derive(Default)]
+pub struct Session {
+    programs: HashMap<u64, Vec<Int>>,
+    vms: HashMap<u64, IntCodeVM>,
+    next_id: u64,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Handles one request line and returns the response line, if the
+    /// request is not a notification.
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(response(Value::Null, Err(error(PARSE_ERROR, e.to_string())))),
+        };
+        let id = request.get("id").cloned();
+        let result = match (request.get("method").and_then(Value::as_str), request.get("params")) {
+            (Some(method), Some(Value::Object(params))) => self.dispatch(method, params),
+            (Some(method), None) => self.dispatch(method, &Map::new()),
+            (Some(_), Some(_)) => Err(error(INVALID_PARAMS, "expected params to be an object")),
+            // not a request at all, so not a notification either
+            (None, _) => return Some(response(id.unwrap_or(Value::Null), Err(error(INVALID_REQUEST, "expected an object with a method")))),
+        };
+        id.map(|id| response(id, result))
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Map<String, Value>) -> Result<Value, RpcError> {
+        match method {
+            "load_program" => {
+                let program = match params.get("source") {
+                    Some(Value::String(source)) => loader::parse_text(source),
+                    _ => return Err(error(INVALID_PARAMS, "expected the program text as `source`")),
+                }.map_err(|e| error(INVALID_PARAMS, e.to_string()))?;
+                let length = program.len();
+                let id = self.fresh_id();
+                self.programs.insert(id, program);
+                Ok(json!({ "program": id, "length": length }))
+            }
+            "create_vm" => {
+                let program = self.programs.get(&uint(params, "program")?)
+                    .ok_or_else(|| error(UNKNOWN_ID, "no such program"))?;
+                let mut vm = IntCodeVM::new(program, &[]);
+                for value in ints(params, "inputs", true)? {
+                    vm.feed_input(value);
+                }
+                let id = self.fresh_id();
+                self.vms.insert(id, vm);
+                Ok(json!({ "vm": id }))
+            }
+            "send_input" => {
+                let values = ints(params, "values", false)?;
+                let vm = self.vm(params)?;
+                for &value in &values {
+                    vm.feed_input(value);
+                }
+                Ok(json!({ "queued": values.len() }))
+            }
+            "run" => {
+                let budget = match params.get("max_instructions") {
+                    None => DEFAULT_BUDGET,
+                    Some(_) => uint(params, "max_instructions")?,
+                };
+                let vm = self.vm(params)?;
+                let mut executed = 0;
+                loop {
+                    if executed == budget {
+                        return Ok(json!({ "event": "budget_exhausted" }));
+                    }
+                    executed += 1;
+                    match vm.step() {
+                        Ok(Step::Ran) => (),
+                        Ok(Step::Output(value)) => return Ok(json!({ "event": "output", "value": value })),
+                        Ok(Step::NeedInput) => return Ok(json!({ "event": "need_input" })),
+                        Ok(Step::Halted) => return Ok(json!({ "event": "halted" })),
+                        Err(fault) => return Err(error(VM_FAULT, fault.to_string())),
+                    }
+                }
+            }
+            "read_memory" => {
+                let address = uint(params, "address")? as usize;
+                let length = match params.get("length") {
+                    None => 1,
+                    Some(_) => uint(params, "length")?,
+                };
+                if length > MAX_READ {
+                    return Err(error(INVALID_PARAMS, format!("cannot read more than {} cells at once", MAX_READ)));
+                }
+                let vm = self.vm(params)?;
+                let values = (address..address.saturating_add(length as usize))
+                    .map(|addr| vm.read(addr))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|fault| error(VM_FAULT, fault.to_string()))?;
+                Ok(json!({ "values": values }))
+            }
+            "snapshot" => {
+                let copy = self.vm(params)?.clone();
+                let id = self.fresh_id();
+                self.vms.insert(id, copy);
+                Ok(json!({ "vm": id }))
+            }
+            "destroy" => {
+                let id = uint(params, "vm")?;
+                self.vms.remove(&id).ok_or_else(|| error(UNKNOWN_ID, "no such vm"))?;
+                Ok(json!({}))
+            }
+            _ => Err(error(METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+        }
+    }
+
+    fn fresh_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn vm(&mut self, params: &Map<String, Value>) -> Result<&mut IntCodeVM, RpcError> {
+        let id = uint(params, "vm")?;
+        self.vms.get_mut(&id).ok_or_else(|| error(UNKNOWN_ID, "no such vm"))
+    }
+}
+
+fn uint(params: &Map<String, Value>, name: &str) -> Result<u64, RpcError> {
+    params.get(name).and_then(Value::as_u64)
+        .ok_or_else(|| error(INVALID_PARAMS, format!("`{}` must be a non-negative integer", name)))
+}
+
+fn ints(params: &Map<String, Value>, name: &str, optional: bool) -> Result<Vec<Int>, RpcError> {
+    let invalid = || error(INVALID_PARAMS, format!("`{}` must be an array of integers", name));
+    match params.get(name) {
+        None if optional => Ok(Vec::new()),
+        Some(Value::Array(values)) => values.iter().map(|v| v.as_i64().ok_or_else(invalid)).collect(),
+        _ => Err(invalid()),
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": e.code, "message": e.message } }),
+    };
+    response.to_string()
+}
+
+/// Serves one session until `input` is exhausted.
+pub fn serve_stream<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut session = Session::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = session.handle_line(&line) {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes a socket file left behind by a server that is no longer running.
+/// Anything else at `path` is an error.
+fn clear_stale_socket(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let in_use = |why: &str| Err(io::Error::new(io::ErrorKind::AddrInUse, format!("{}: {}", path.display(), why)));
+    if !metadata.file_type().is_socket() {
+        return in_use("exists and is not a socket");
+    }
+    if UnixStream::connect(path).is_ok() {
+        return in_use("another server is listening on it");
+    }
+    fs::remove_file(path)
+}
+
+/// Accepts connections on a Unix domain socket forever, one thread and
+/// session per connection. A socket file from an earlier run is replaced.
+pub fn serve_socket<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    clear_stale_socket(path.as_ref())?;
+    let listener = UnixListener::bind(path)?;
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || {
+            let reader = BufReader::new(stream.try_clone()?);
+            serve_stream(reader, stream)
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(session: &mut Session, request: Value) -> Value {
+        serde_json::from_str(&session.handle_line(&request.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_session() {
+        let mut s = Session::new();
+        let r = call(&mut s, json!({"jsonrpc": "2.0", "id": 1, "method": "load_program", "params": {"source": "3,0,4,0,99"}}));
+        assert_eq!(r["result"], json!({"program": 1, "length": 5}));
+        let r = call(&mut s, json!({"id": 2, "method": "create_vm", "params": {"program": 1}}));
+        assert_eq!(r["result"]["vm"], 2);
+        let r = call(&mut s, json!({"id": 3, "method": "run", "params": {"vm": 2}}));
+        assert_eq!(r["result"], json!({"event": "need_input"}));
+
+        let r = call(&mut s, json!({"id": 4, "method": "snapshot", "params": {"vm": 2}}));
+        assert_eq!(r["result"]["vm"], 3);
+        call(&mut s, json!({"id": 5, "method": "send_input", "params": {"vm": 2, "values": [42]}}));
+        call(&mut s, json!({"id": 6, "method": "send_input", "params": {"vm": 3, "values": [7]}}));
+        let r = call(&mut s, json!({"id": 7, "method": "run", "params": {"vm": 2}}));
+        assert_eq!(r["result"], json!({"event": "output", "value": 42}));
+        let r = call(&mut s, json!({"id": 8, "method": "run", "params": {"vm": 3}}));
+        assert_eq!(r["result"], json!({"event": "output", "value": 7}));
+        let r = call(&mut s, json!({"id": 9, "method": "read_memory", "params": {"vm": 2, "address": 0, "length": 3}}));
+        assert_eq!(r["result"], json!({"values": [42, 0, 4]}));
+        let r = call(&mut s, json!({"id": 10, "method": "run", "params": {"vm": 2}}));
+        assert_eq!(r["result"], json!({"event": "halted"}));
+
+        let r = call(&mut s, json!({"id": 11, "method": "destroy", "params": {"vm": 2}}));
+        assert_eq!(r["result"], json!({}));
+        let r = call(&mut s, json!({"id": 12, "method": "run", "params": {"vm": 2}}));
+        assert_eq!(r["error"]["code"], UNKNOWN_ID);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut s = Session::new();
+        let r: Value = serde_json::from_str(&s.handle_line("{nope").unwrap()).unwrap();
+        assert_eq!(r["error"]["code"], PARSE_ERROR);
+        let r = call(&mut s, json!({"id": 1, "params": {}}));
+        assert_eq!(r["error"]["code"], INVALID_REQUEST);
+        let r = call(&mut s, json!({"id": 1, "method": "destroy", "params": [2]}));
+        assert_eq!(r["error"]["code"], INVALID_PARAMS);
+        let r = call(&mut s, json!({"id": 1, "method": "fly"}));
+        assert_eq!(r["error"]["code"], METHOD_NOT_FOUND);
+        let r = call(&mut s, json!({"id": 2, "method": "load_program", "params": {"source": "1,x"}}));
+        assert_eq!(r["error"]["code"], INVALID_PARAMS);
+        let r = call(&mut s, json!({"id": 2, "method": "load_program", "params": {"path": "/etc/passwd"}}));
+        assert_eq!(r["error"]["code"], INVALID_PARAMS);
+        call(&mut s, json!({"id": 3, "method": "load_program", "params": {"source": "1105,1,0"}}));
+        call(&mut s, json!({"id": 4, "method": "create_vm", "params": {"program": 1}}));
+        let r = call(&mut s, json!({"id": 5, "method": "run", "params": {"vm": 2, "max_instructions": 100}}));
+        assert_eq!(r["result"], json!({"event": "budget_exhausted"}));
+        let r = call(&mut s, json!({"id": 6, "method": "run", "params": {"vm": 2}}));
+        assert_eq!(r["result"], json!({"event": "budget_exhausted"}));
+
+        // notifications are carried out without a reply, even when they fail
+        let mut s = Session::new();
+        assert_eq!(s.handle_line(r#"{"method": "load_program", "params": {"source": "99"}}"#), None);
+        assert_eq!(s.handle_line(r#"{"method": "fly"}"#), None);
+        let r = call(&mut s, json!({"id": 1, "method": "create_vm", "params": {"program": 1}}));
+        assert_eq!(r["result"], json!({"vm": 2}));
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut s = Session::new();
+        call(&mut s, json!({"id": 1, "method": "load_program", "params": {"source": "1101,1,1,0,104,7,99"}}));
+        call(&mut s, json!({"id": 2, "method": "create_vm", "params": {"program": 1}}));
+        let r = call(&mut s, json!({"id": 3, "method": "run", "params": {"vm": 2, "max_instructions": 1}}));
+        assert_eq!(r["result"], json!({"event": "budget_exhausted"}));
+        let r = call(&mut s, json!({"id": 4, "method": "run", "params": {"vm": 2}}));
+        assert_eq!(r["result"], json!({"event": "output", "value": 7}));
+    }
+
+    #[test]
+    fn test_socket() {
+        let path = std::env::temp_dir().join(format!("intcode-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener_path = path.clone();
+        thread::spawn(move || serve_socket(listener_path));
+        let stream = loop {
+            match UnixStream::connect(&path) {
+                Ok(stream) => break stream,
+                Err(_) => thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        writeln!(writer, r#"{{"jsonrpc":"2.0","id":1,"method":"load_program","params":{{"source":"104,5,99"}}}}"#).unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let r: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(r["result"]["length"], 3);
+        assert_eq!(serve_socket(&path).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stale_socket() {
+        let path = std::env::temp_dir().join(format!("intcode-stale-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        // a listener that is gone leaves its socket file behind
+        drop(UnixListener::bind(&path).unwrap());
+        clear_stale_socket(&path).unwrap();
+        assert!(!path.exists());
+        std::fs::write(&path, "not a socket").unwrap();
+        assert_eq!(clear_stale_socket(&path).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
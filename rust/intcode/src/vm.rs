@@ -0,0 +1,383 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::FromIterator;
+
+pub type Int = i64;
+
+/// Memory cells a VM may grow to unless told otherwise (128 MiB).
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+#[derive(Debug)]
+pub enum Instruction {
+    Add(Int, Int, usize),
+    Eq(Int, Int, usize),
+    Hlt,
+    Inp(usize),
+    Jnz(Int, usize),
+    Jz(Int, usize),
+    Lt(Int, Int, usize),
+    Mul(Int, Int, usize),
+    Out(Int),
+    Rbo(Int),
+}
+
+impl Instruction {
+    /// Instruction length, number of parameters it reads and whether its
+    /// last parameter is a destination.
+    fn shape(&self) -> (usize, usize, bool) {
+        use Instruction::*;
+        match self {
+            Add(..) | Mul(..) | Lt(..) | Eq(..) => (4, 2, true),
+            Jnz(..) | Jz(..) => (3, 2, false),
+            Inp(_) => (2, 0, true),
+            Out(_) | Rbo(_) => (2, 1, false),
+            Hlt => (1, 0, false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Ran,
+    Output(Int),
+    NeedInput,
+    Halted,
+}
+
+/// Reasons a program can crash the VM. The faulting instruction is not
+/// executed, so `pc` still points at it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    UnknownOpcode { pc: usize, opcode: Int },
+    ImmediateDestination { pc: usize },
+    AddressOutOfRange { pc: usize, addr: Int },
+    MemoryLimit { pc: usize, addr: Int },
+    Overflow { pc: usize },
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Fault::*;
+        match self {
+            UnknownOpcode { pc, opcode } => write!(f, "unknown opcode {} at {}", opcode, pc),
+            ImmediateDestination { pc } => write!(f, "immediate output parameter at {}", pc),
+            AddressOutOfRange { pc, addr } => write!(f, "address {} out of range at {}", addr, pc),
+            MemoryLimit { pc, addr } => write!(f, "address {} exceeds the memory limit at {}", addr, pc),
+            Overflow { pc } => write!(f, "arithmetic overflow at {}", pc),
+        }
+    }
+}
+
+impl std::error::Error for Fault {}
+
+/// Per-cell access counters, only kept when enabled with `track_activity`.
+#[derive(Clone, Default)]
+pub struct Activity {
+    pub reads: Vec<u32>,
+    pub writes: Vec<u32>,
+    pub executes: Vec<u32>,
+}
+
+impl Activity {
+    fn new(size: usize) -> Activity {
+        Activity { reads: vec![0; size], writes: vec![0; size], executes: vec![0; size] }
+    }
+
+    fn grow(&mut self, size: usize) {
+        if size > self.executes.len() {
+            self.reads.resize(size, 0);
+            self.writes.resize(size, 0);
+            self.executes.resize(size, 0);
+        }
+    }
+}
+
+/// Memory starts out as a copy of the program and grows when a program
+/// writes past its end, up to the memory limit. Cells never written read as 0.
+#[derive(Clone)]
+pub struct IntCodeVM {
+    pc: usize,
+    mem: Vec<Int>,
+    memory_limit: usize,
+    inputs: VecDeque<Int>,
+    relative_base: Int,
+    instructions: u64,
+    activity: Option<Activity>,
+}
+
+impl IntCodeVM {
+    pub fn new(starting_memory: &[Int], inputbuffer: &[Int]) -> IntCodeVM {
+        IntCodeVM {
+            pc: 0,
+            mem: starting_memory.to_vec(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            inputs: VecDeque::from_iter(inputbuffer.iter().copied()),
+            relative_base: 0,
+            instructions: 0,
+            activity: None,
+        }
+    }
+
+    pub fn set_memory_limit(&mut self, cells: usize) {
+        self.memory_limit = cells;
+    }
+
+    pub fn track_activity(&mut self) {
+        self.activity = Some(Activity::new(self.mem.len()));
+    }
+
+    pub fn activity(&self) -> Option<&Activity> {
+        self.activity.as_ref()
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn relative_base(&self) -> Int {
+        self.relative_base
+    }
+
+    /// The memory allocated so far.
+    pub fn memory(&self) -> &[Int] {
+        &self.mem
+    }
+
+    /// Number of instructions executed so far.
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    pub fn read(&self, addr: usize) -> Result<Int, Fault> {
+        if addr >= self.memory_limit {
+            return Err(Fault::MemoryLimit { pc: self.pc, addr: addr as Int });
+        }
+        Ok(self.load(addr))
+    }
+
+    pub fn write(&mut self, addr: usize, value: Int) -> Result<(), Fault> {
+        if addr >= self.memory_limit {
+            return Err(Fault::MemoryLimit { pc: self.pc, addr: addr as Int });
+        }
+        self.store(addr, value);
+        Ok(())
+    }
+
+    /// Executes a single instruction. An input instruction with nothing
+    /// buffered is not executed, so the VM can be resumed after `feed_input`.
+    pub fn step(&mut self) -> Result<Step, Fault> {
+        use Instruction::*;
+        let instruction = self.current_opcode_decode()?;
+        if let Inp(_) = instruction {
+            if self.inputs.is_empty() {
+                return Ok(Step::NeedInput);
+            }
+        }
+        if self.activity.is_some() {
+            self.record_activity(&instruction)?;
+        }
+        let overflow = Fault::Overflow { pc: self.pc };
+        let step = match instruction {
+            Add(a, b, out) => { self.store(out, a.checked_add(b).ok_or(overflow)?); self.pc += 4; Step::Ran }
+            Mul(a, b, out) => { self.store(out, a.checked_mul(b).ok_or(overflow)?); self.pc += 4; Step::Ran }
+            Inp(out) => { let i = self.inputs.pop_back().unwrap(); self.store(out, i); self.pc += 2; Step::Ran }
+            Out(a) => { self.pc += 2; Step::Output(a) }
+            Jnz(a, b) => { self.pc = if a != 0 { b } else { self.pc + 3 }; Step::Ran }
+            Jz(a, b) => { self.pc = if a == 0 { b } else { self.pc + 3 }; Step::Ran }
+            Lt(a, b, out) => { self.store(out, if a < b { 1 } else { 0 }); self.pc += 4; Step::Ran }
+            Eq(a, b, out) => { self.store(out, if a == b { 1 } else { 0 }); self.pc += 4; Step::Ran }
+            Rbo(a) => { self.relative_base = self.relative_base.checked_add(a).ok_or(overflow)?; self.pc += 2; Step::Ran }
+            Hlt => Step::Halted
+        };
+        self.instructions += 1;
+        Ok(step)
+    }
+
+    pub fn next_output(&mut self) -> Option<Int> {
+        self.next_output_observed(|_| ())
+    }
+
+    /// Like `next_output`, calling `observe` after every executed instruction.
+    pub fn next_output_observed<F: FnMut(&IntCodeVM)>(&mut self, mut observe: F) -> Option<Int> {
+        loop {
+            let step = self.step().unwrap_or_else(|e| panic!("{}", e));
+            observe(self);
+            match step {
+                Step::Ran => (),
+                Step::Output(a) => return Some(a),
+                Step::Halted => return None,
+                Step::NeedInput => panic!("input requested but none was fed")
+            }
+        }
+    }
+
+    pub fn collect_output(&mut self) -> Vec<Int> {
+        let mut out = Vec::new();
+        while let Some(n) = self.next_output() {
+            out.push(n)
+        }
+        out
+    }
+
+    pub fn feed_input(&mut self, i: Int) {
+        self.inputs.push_front(i);
+    }
+
+    fn load(&self, addr: usize) -> Int {
+        self.mem.get(addr).copied().unwrap_or(0)
+    }
+
+    fn store(&mut self, addr: usize, value: Int) {
+        if addr >= self.mem.len() {
+            self.mem.resize(addr + 1, 0);
+        }
+        self.mem[addr] = value;
+    }
+
+    fn mode(&self, n: usize) -> Int {
+        self.load(self.pc) / (10 * 10_u32.pow(n as u32) as Int) % 10
+    }
+
+    fn address(&self, addr: Int) -> Result<usize, Fault> {
+        if addr < 0 {
+            return Err(Fault::AddressOutOfRange { pc: self.pc, addr });
+        }
+        if addr as u64 >= self.memory_limit as u64 {
+            return Err(Fault::MemoryLimit { pc: self.pc, addr });
+        }
+        Ok(addr as usize)
+    }
+
+    /// Address parameter `n` refers to, `None` for immediate parameters.
+    fn param_addr(&self, n: usize) -> Result<Option<usize>, Fault> {
+        let p = self.load(self.address((self.pc + n) as Int)?);
+        match self.mode(n) {
+            1 => Ok(None),
+            2 => self.address(self.relative_base.saturating_add(p)).map(Some),
+            _ => self.address(p).map(Some)
+        }
+    }
+
+    fn record_activity(&mut self, instruction: &Instruction) -> Result<(), Fault> {
+        let (len, reads, writes) = instruction.shape();
+        let mut read_addrs = Vec::new();
+        for n in 1..=reads {
+            read_addrs.extend(self.param_addr(n)?);
+        }
+        let write_addr = if writes { self.param_addr(len - 1)? } else { None };
+        let activity = self.activity.as_mut().unwrap();
+        let highest = read_addrs.iter().chain(write_addr.iter()).fold(self.pc + len, |m, &a| m.max(a + 1));
+        activity.grow(highest);
+        for addr in self.pc..self.pc + len {
+            activity.executes[addr] += 1;
+        }
+        for addr in read_addrs {
+            activity.reads[addr] += 1;
+        }
+        if let Some(addr) = write_addr {
+            activity.writes[addr] += 1;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn current_opcode_decode(&self) -> Result<Instruction, Fault> {
+        let pc = self.pc;
+        let param = |n: usize| -> Result<Int, Fault> {
+            match self.param_addr(n)? {
+                Some(addr) => Ok(self.load(addr)),
+                None => Ok(self.load(pc + n))
+            }
+        };
+
+        let dest = |n: usize| -> Result<usize, Fault> {
+            match self.param_addr(n)? {
+                Some(addr) => Ok(addr),
+                None => Err(Fault::ImmediateDestination { pc })
+            }
+        };
+
+        let target = |n: usize| -> Result<usize, Fault> {
+            let addr = param(n)?;
+            if addr < 0 {
+                return Err(Fault::AddressOutOfRange { pc, addr });
+            }
+            Ok(addr as usize)
+        };
+
+        use Instruction::*;
+        let opcode = self.load(self.address(pc as Int)?);
+        Ok(match opcode % 100 {
+            1 => Add(param(1)?, param(2)?, dest(3)?),
+            2 => Mul(param(1)?, param(2)?, dest(3)?),
+            3 => Inp(dest(1)?),
+            4 => Out(param(1)?),
+            5 => Jnz(param(1)?, target(2)?),
+            6 => Jz(param(1)?, target(2)?),
+            7 => Lt(param(1)?, param(2)?, dest(3)?),
+            8 => Eq(param(1)?, param(2)?, dest(3)?),
+            9 => Rbo(param(1)?),
+            99 => Hlt,
+            _ => return Err(Fault::UnknownOpcode { pc, opcode })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_waits_for_input() {
+        let mut vm = IntCodeVM::new(&[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0], &[]);
+        assert_eq!(vm.step(), Ok(Step::NeedInput));
+        assert_eq!(vm.pc(), 0);
+        vm.feed_input(41);
+        assert_eq!(vm.step(), Ok(Step::Ran));
+        assert_eq!(vm.step(), Ok(Step::Ran));
+        assert_eq!(vm.step(), Ok(Step::Output(42)));
+        assert_eq!(vm.step(), Ok(Step::Halted));
+    }
+
+    #[test]
+    fn test_faults() {
+        let mut vm = IntCodeVM::new(&[1101, 1, 1, 5, 42], &[]);
+        assert_eq!(vm.step(), Ok(Step::Ran));
+        assert_eq!(vm.step(), Err(Fault::UnknownOpcode { pc: 4, opcode: 42 }));
+        assert_eq!(vm.pc(), 4);
+
+        let mut vm = IntCodeVM::new(&[4, -3, 99], &[]);
+        assert_eq!(vm.step(), Err(Fault::AddressOutOfRange { pc: 0, addr: -3 }));
+
+        let mut vm = IntCodeVM::new(&[11101, 1, 1, 5, 99], &[]);
+        assert_eq!(vm.step(), Err(Fault::ImmediateDestination { pc: 0 }));
+
+        let mut vm = IntCodeVM::new(&[1102, i64::MAX, 2, 0, 99], &[]);
+        assert_eq!(vm.step(), Err(Fault::Overflow { pc: 0 }));
+    }
+
+    #[test]
+    fn test_memory_growth() {
+        let mut vm = IntCodeVM::new(&[1101, 2, 3, 100, 4, 100, 4, 5000, 99], &[]);
+        assert_eq!(vm.collect_output(), vec![5, 0]);
+        assert_eq!(vm.memory().len(), 101);
+        assert_eq!(vm.instructions(), 4);
+
+        let mut vm = IntCodeVM::new(&[1101, 2, 3, 100, 99], &[]);
+        vm.set_memory_limit(100);
+        assert_eq!(vm.step(), Err(Fault::MemoryLimit { pc: 0, addr: 100 }));
+        assert_eq!(vm.memory().len(), 5);
+    }
+
+    #[test]
+    fn test_activity() {
+        let mut vm = IntCodeVM::new(&[1101, 2, 3, 7, 4, 7, 99, 0], &[]);
+        vm.track_activity();
+        assert_eq!(vm.collect_output(), vec![5]);
+        let activity = vm.activity().unwrap();
+        assert_eq!(activity.executes[..7], [1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(activity.writes[7], 1);
+        assert_eq!(activity.reads[7], 1);
+        assert_eq!(activity.reads[1], 0);
+    }
+}
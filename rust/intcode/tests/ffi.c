@@ -0,0 +1,47 @@
+#include <stdio.h>
+#include "intcode.h"
+
+#define CHECK(cond) do { if (!(cond)) { fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #cond); return 1; } } while (0)
+
+int main(void) {
+    /* day09 quine */
+    int64_t quine[] = {109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99};
+    size_t len = sizeof(quine) / sizeof(quine[0]);
+    IntCodeVM *vm = intcode_vm_new(quine, len);
+    CHECK(vm != NULL);
+    for (size_t i = 0; i < len; i++) {
+        int64_t out = 0;
+        CHECK(intcode_vm_run(vm, &out) == INTCODE_OUTPUT);
+        CHECK(out == quine[i]);
+    }
+    CHECK(intcode_vm_run(vm, NULL) == INTCODE_HALTED);
+    intcode_vm_free(vm);
+
+    /* echo one input, then poke memory to change the result */
+    int64_t echo[] = {3, 0, 4, 0, 99};
+    vm = intcode_vm_new(echo, 5);
+    int64_t value = 0;
+    CHECK(intcode_vm_step(vm, NULL) == INTCODE_NEED_INPUT);
+    CHECK(intcode_vm_push_input(vm, 42) == INTCODE_OK);
+    CHECK(intcode_vm_step(vm, NULL) == INTCODE_OK);
+    CHECK(intcode_vm_read_memory(vm, 0, &value) == INTCODE_OK && value == 42);
+    CHECK(intcode_vm_write_memory(vm, 0, 7) == INTCODE_OK);
+    CHECK(intcode_vm_step(vm, &value) == INTCODE_OUTPUT && value == 7);
+    CHECK(intcode_vm_read_memory(vm, 1000, &value) == INTCODE_OK && value == 0);
+    CHECK(intcode_vm_write_memory(vm, 1000, 5) == INTCODE_OK);
+    CHECK(intcode_vm_memory_size(vm) == 1001);
+    CHECK(intcode_vm_read_memory(vm, SIZE_MAX, &value) == INTCODE_ERR_MEMORY_LIMIT);
+    intcode_vm_free(vm);
+
+    /* errors instead of crashes */
+    int64_t bad[] = {42};
+    vm = intcode_vm_new(bad, 1);
+    CHECK(intcode_vm_run(vm, NULL) == INTCODE_ERR_UNKNOWN_OPCODE);
+    CHECK(intcode_vm_run(vm, NULL) == INTCODE_ERR_UNKNOWN_OPCODE);
+    intcode_vm_free(vm);
+    CHECK(intcode_vm_run(NULL, NULL) == INTCODE_ERR_NULL);
+    CHECK(intcode_vm_new(NULL, 3) == NULL);
+
+    puts("ok");
+    return 0;
+}
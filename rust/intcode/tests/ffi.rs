@@ -0,0 +1,28 @@
+//! Builds `tests/ffi.c` against the cdylib and runs it.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_c_client() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // the cdylib is built next to the test binary in target/<profile>/deps
+    let lib_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let exe = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi_test");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_owned()))
+        .arg(manifest_dir.join("tests/ffi.c"))
+        .arg("-I").arg(manifest_dir.join("include"))
+        .arg("-L").arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lintcode")
+        .arg("-o").arg(&exe)
+        .status()
+        .expect("cannot run the C compiler");
+    assert!(status.success());
+
+    let output = Command::new(&exe).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.stdout, b"ok\n");
+}
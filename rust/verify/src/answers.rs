@@ -0,0 +1,194 @@
+//! The answer registry. `answers.toml` lists runs of a day's solver, each
+//! with the input fed to it on stdin, any extra arguments and the expected
+//! answer of each part:
+//!
+//! ```toml
+//! [[answer]]
+//! day = 3
+//! input = "day03/example1.txt"
+//! part1 = 6
+//! part2 = 30
+//! ```
+//!
+//! Inputs are relative to the `inputs` directory next to the registry.
+//! Answers are numbers or strings, and a part that is left out is not
+//! checked.
+
+use std::fmt;
+
+use toml::Value;
+
+const KEYS: &[&str] = &["day", "input", "args", "part1", "part2"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub day: u32,
+    pub input: Option<String>,
+    pub args: Vec<String>,
+    /// Expected answers by part number.
+    pub parts: Vec<(u32, String)>,
+}
+
+impl Answer {
+    /// The name of the day's binary.
+    pub fn binary(&self) -> String {
+        format!("day{:02}", self.day)
+    }
+
+    /// How the run is called in reports.
+    pub fn name(&self) -> String {
+        let mut name = self.input.clone().unwrap_or_else(|| self.binary());
+        for arg in &self.args {
+            name.push(' ');
+            name.push_str(arg);
+        }
+        name
+    }
+}
+
+#[derive(Debug)]
+pub enum AnswerError {
+    Toml(toml::de::Error),
+    /// Something wrong with the `index`th answer.
+    Invalid { index: usize, message: String },
+}
+
+impl fmt::Display for AnswerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswerError::Toml(e) => write!(f, "{}", e),
+            AnswerError::Invalid { index, message } => write!(f, "answer {}: {}", index + 1, message),
+        }
+    }
+}
+
+impl std::error::Error for AnswerError {}
+
+fn answer(index: usize, entry: &Value) -> Result<Answer, AnswerError> {
+    let invalid = |message: String| AnswerError::Invalid { index, message };
+    let table = entry.as_table().ok_or_else(|| invalid("expected a table".to_string()))?;
+    if let Some(key) = table.keys().find(|k| !KEYS.contains(&k.as_str())) {
+        return Err(invalid(format!("unknown key `{}`", key)));
+    }
+    let day = match table.get("day").and_then(Value::as_integer) {
+        Some(day) if (1..=25).contains(&day) => day as u32,
+        _ => return Err(invalid("expected `day` between 1 and 25".to_string())),
+    };
+    let input = match table.get("input") {
+        None => None,
+        Some(Value::String(input)) => Some(input.clone()),
+        Some(_) => return Err(invalid("expected `input` to be a path".to_string())),
+    };
+    let args = match table.get("args").map(Value::as_array) {
+        None => Vec::new(),
+        Some(Some(args)) if args.iter().all(Value::is_str) => args.iter().map(|a| a.as_str().unwrap().to_string()).collect(),
+        Some(_) => return Err(invalid("expected `args` to be a list of strings".to_string())),
+    };
+    let mut parts = Vec::new();
+    for part in 1..=2 {
+        match table.get(&format!("part{}", part)) {
+            None => {}
+            Some(Value::String(s)) => parts.push((part, s.clone())),
+            Some(Value::Integer(n)) => parts.push((part, n.to_string())),
+            Some(_) => return Err(invalid(format!("expected `part{}` to be a number or a string", part))),
+        }
+    }
+    if parts.is_empty() {
+        return Err(invalid("expected `part1` or `part2`".to_string()));
+    }
+    Ok(Answer { day, input, args, parts })
+}
+
+/// Reads the registry.
+pub fn parse(text: &str) -> Result<Vec<Answer>, AnswerError> {
+    let registry = text.parse::<Value>().map_err(AnswerError::Toml)?;
+    match registry.get("answer") {
+        None => Ok(Vec::new()),
+        Some(Value::Array(entries)) => entries.iter().enumerate().map(|(i, entry)| answer(i, entry)).collect(),
+        Some(_) => Err(AnswerError::Invalid { index: 0, message: "expected [[answer]] tables".to_string() }),
+    }
+}
+
+/// The `Part N: answer` lines a solver printed, by part. Everything else it
+/// prints is ignored.
+pub fn parts(output: &str) -> Vec<(u32, String)> {
+    output.lines().filter_map(|line| {
+        let rest = line.strip_prefix("Part ")?;
+        let colon = rest.find(':')?;
+        let part = rest[..colon].parse().ok()?;
+        Some((part, rest[colon + 1..].trim().to_string()))
+    }).collect()
+}
+
+/// The expected and found lines of every part that did not match, empty
+/// when they all did.
+pub fn diff(expected: &[(u32, String)], found: &[(u32, String)]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (part, want) in expected {
+        let got = found.iter().find(|(p, _)| p == part).map(|(_, answer)| answer);
+        if got != Some(want) {
+            lines.push(format!("-Part {}: {}", part, want));
+            match got {
+                Some(got) => lines.push(format!("+Part {}: {}", part, got)),
+                None => lines.push(format!("+Part {} missing", part)),
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse() {
+        let answers = parse("
+            [[answer]]
+            day = 4
+            args = ['1-99']
+            part1 = 9
+
+            [[answer]]
+            day = 8
+            input = 'day08/hello.txt'
+            part2 = 'HELLO'
+        ").unwrap();
+        assert_eq!(answers[0], Answer { day: 4, input: None, args: vec!["1-99".to_string()], parts: vec![(1, "9".to_string())] });
+        assert_eq!((answers[0].binary(), answers[0].name()), ("day04".to_string(), "day04 1-99".to_string()));
+        assert_eq!(answers[1].parts, vec![(2, "HELLO".to_string())]);
+        assert_eq!(answers[1].name(), "day08/hello.txt");
+
+        let error = parse("[[answer]]\nday = 1\npart_1 = 3\n").unwrap_err();
+        assert_eq!(error.to_string(), "answer 1: unknown key `part_1`");
+        let error = parse("[[answer]]\nday = 1\npart1 = 3\n[[answer]]\nday = 26\npart1 = 3\n").unwrap_err();
+        assert_eq!(error.to_string(), "answer 2: expected `day` between 1 and 25");
+    }
+
+    #[test]
+    fn test_diff() {
+        let found = parts("Part 1: 296\nPart 2: HELLO\n█  █ ████\n");
+        assert_eq!(found, vec![(1, "296".to_string()), (2, "HELLO".to_string())]);
+        assert!(diff(&found, &found).is_empty());
+        let expected = vec![(1, "295".to_string()), (2, "HELLO".to_string())];
+        assert_eq!(diff(&expected, &found), vec!["-Part 1: 295", "+Part 1: 296"]);
+        assert_eq!(diff(&expected, &found[1..]), vec!["-Part 1: 295", "+Part 1 missing"]);
+    }
+
+    #[test]
+    fn test_registry() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let answers = parse(&fs::read_to_string(root.join("answers.toml")).unwrap()).unwrap();
+        for answer in &answers {
+            if let Some(input) = &answer.input {
+                assert!(root.join("inputs").join(input).is_file(), "{} is missing", input);
+            }
+        }
+        let mut days = answers.iter().map(|a| a.day).collect::<Vec<_>>();
+        days.dedup();
+        assert_eq!(days, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 11]);
+    }
+}
@@ -0,0 +1,4 @@
+//! Checking every day's solver against the answers recorded for the stored
+//! inputs.
+
+pub mod answers;
@@ -0,0 +1,83 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+use verify::answers::{self, Answer};
+
+fn usage() -> ! {
+    eprintln!("usage: verify [--answers FILE] [DAY]...");
+    eprintln!("runs the day binaries built next to this one, so build the workspace first");
+    process::exit(2)
+}
+
+/// Runs one answer's solver, giving the diff lines if it did not match.
+fn check(answer: &Answer, bin: &Path, inputs: &Path) -> Result<Vec<String>, String> {
+    let binary = bin.join(answer.binary());
+    let mut command = Command::new(&binary);
+    command.args(&answer.args).stderr(Stdio::piped());
+    match &answer.input {
+        Some(input) => {
+            let path = inputs.join(input);
+            command.stdin(File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?);
+        }
+        None => {
+            command.stdin(Stdio::null());
+        }
+    }
+    let output = command.output().map_err(|e| format!("{}: {}", binary.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("{} {}\n{}", answer.binary(), output.status, String::from_utf8_lossy(&output.stderr).trim_end()));
+    }
+    Ok(answers::diff(&answer.parts, &answers::parts(&String::from_utf8_lossy(&output.stdout))))
+}
+
+fn main() {
+    let mut registry = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../answers.toml");
+    let mut days = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--answers" => registry = PathBuf::from(args.next().unwrap_or_else(|| usage())),
+            day => days.push(day.trim_start_matches("day").parse::<u32>().unwrap_or_else(|_| usage())),
+        }
+    }
+
+    let text = fs::read_to_string(&registry).unwrap_or_else(|e| {
+        eprintln!("{}: {}", registry.display(), e);
+        process::exit(1)
+    });
+    let answers = answers::parse(&text).unwrap_or_else(|e| {
+        eprintln!("{}: {}", registry.display(), e);
+        process::exit(1)
+    });
+    let inputs = registry.parent().unwrap().join("inputs");
+    let bin = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)).unwrap_or_default();
+
+    let mut failed = 0;
+    let mut checked = 0;
+    for answer in answers.iter().filter(|a| days.is_empty() || days.contains(&a.day)) {
+        checked += 1;
+        match check(answer, &bin, &inputs) {
+            Ok(diff) if diff.is_empty() => println!("ok      {}", answer.name()),
+            Ok(diff) => {
+                failed += 1;
+                println!("FAILED  {}", answer.name());
+                for line in diff {
+                    println!("    {}", line);
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("FAILED  {}", answer.name());
+                for line in e.lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
+    }
+    println!("{} checked, {} failed", checked, failed);
+    if failed > 0 {
+        process::exit(1);
+    }
+}